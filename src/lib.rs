@@ -12,6 +12,7 @@
 #![cfg_attr(test, feature(io))]
 
 extern crate alloc;
+extern crate time;
 
 #[macro_use]
 extern crate log;
@@ -0,0 +1,330 @@
+use super::{Queue, SyncQueue};
+use std::sync::{Arc, Mutex, Condvar};
+
+/// A bounded queue in which values are contained by a fixed size ring buffer.
+///
+/// All of the storage is allocated when the queue is created and the queue
+/// never grows. The current implementation is based on a single mutex and two
+/// condition variables and shares the blocking semantics of `LinkedQueue`.
+pub struct ArrayQueue<T> {
+    inner: Arc<QueueInner<T>>,
+}
+
+impl<T: Send> ArrayQueue<T> {
+    pub fn with_capacity(capacity: usize) -> ArrayQueue<T> {
+        assert!(capacity > 0, "capacity must be greater than zero");
+
+        ArrayQueue {
+            inner: Arc::new(QueueInner::new(capacity))
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.inner.capacity
+    }
+
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn offer(&self, e: T) -> Result<(), T> {
+        self.inner.offer(e)
+    }
+
+    /// Inserts into the queue, blocking until there is space available.
+    pub fn put(&self, e: T) {
+        self.inner.put(e);
+    }
+
+    pub fn poll(&self) -> Option<T> {
+        self.inner.poll()
+    }
+
+    /// Takes from the queue, blocking until there is an element available.
+    pub fn take(&self) -> T {
+        self.inner.take()
+    }
+}
+
+impl<T: Send> Queue<T> for ArrayQueue<T> {
+    fn poll(&self) -> Option<T> {
+        ArrayQueue::poll(self)
+    }
+
+    fn len(&self) -> usize {
+        ArrayQueue::len(self)
+    }
+
+    fn capacity(&self) -> Option<usize> {
+        Some(ArrayQueue::capacity(self))
+    }
+
+    fn is_empty(&self) -> bool {
+        ArrayQueue::is_empty(self)
+    }
+
+    fn offer(&self, e: T) -> Result<(), T> {
+        ArrayQueue::offer(self, e)
+    }
+}
+
+impl<T: Send> SyncQueue<T> for ArrayQueue<T> {
+    fn take(&self) -> T {
+        ArrayQueue::take(self)
+    }
+
+    fn put(&self, e: T) {
+        ArrayQueue::put(self, e)
+    }
+}
+
+impl<T: Send> Clone for ArrayQueue<T> {
+    fn clone(&self) -> ArrayQueue<T> {
+        ArrayQueue { inner: self.inner.clone() }
+    }
+}
+
+struct QueueInner<T> {
+
+    // Maximum number of elements the queue can contain at one time
+    capacity: usize,
+
+    // The ring buffer, guarded by a single lock for both puts and takes
+    ring: Mutex<Ring<T>>,
+
+    // Wait queue for waiting takes
+    not_empty: Condvar,
+
+    // Wait queue for waiting puts
+    not_full: Condvar,
+}
+
+impl<T: Send> QueueInner<T> {
+    fn new(capacity: usize) -> QueueInner<T> {
+        QueueInner {
+            capacity: capacity,
+            ring: Mutex::new(Ring::new(capacity)),
+            not_empty: Condvar::new(),
+            not_full: Condvar::new(),
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.ring.lock()
+            .ok().expect("something went wrong")
+            .len
+    }
+
+    fn offer(&self, e: T) -> Result<(), T> {
+        let mut ring = self.ring.lock()
+            .ok().expect("something went wrong");
+
+        if ring.is_full() {
+            return Err(e);
+        }
+
+        ring.push(e);
+        self.not_empty.notify_one();
+
+        Ok(())
+    }
+
+    fn put(&self, e: T) {
+        let mut ring = self.ring.lock()
+            .ok().expect("something went wrong");
+
+        while ring.is_full() {
+            ring = self.not_full.wait(ring)
+                .ok().expect("something went wrong");
+        }
+
+        ring.push(e);
+        self.not_empty.notify_one();
+    }
+
+    fn poll(&self) -> Option<T> {
+        let mut ring = self.ring.lock()
+            .ok().expect("something went wrong");
+
+        if ring.len == 0 {
+            return None;
+        }
+
+        let val = ring.pop();
+        self.not_full.notify_one();
+
+        Some(val)
+    }
+
+    fn take(&self) -> T {
+        let mut ring = self.ring.lock()
+            .ok().expect("something went wrong");
+
+        while ring.len == 0 {
+            ring = self.not_empty.wait(ring)
+                .ok().expect("something went wrong");
+        }
+
+        let val = ring.pop();
+        self.not_full.notify_one();
+
+        val
+    }
+}
+
+struct Ring<T> {
+    // Preallocated slots, None when the slot is vacant
+    buf: Vec<Option<T>>,
+
+    // Index of the head of the queue
+    head: usize,
+
+    // Current number of elements
+    len: usize,
+}
+
+impl<T: Send> Ring<T> {
+    fn new(capacity: usize) -> Ring<T> {
+        Ring {
+            buf: range(0, capacity).map(|_| None).collect(),
+            head: 0,
+            len: 0,
+        }
+    }
+
+    fn is_full(&self) -> bool {
+        self.len == self.buf.len()
+    }
+
+    fn push(&mut self, e: T) {
+        let idx = (self.head + self.len) % self.buf.len();
+
+        debug_assert!(self.buf[idx].is_none());
+
+        self.buf[idx] = Some(e);
+        self.len += 1;
+    }
+
+    fn pop(&mut self) -> T {
+        let idx = self.head;
+
+        self.head = (self.head + 1) % self.buf.len();
+        self.len -= 1;
+
+        self.buf[idx].take().expect("item already consumed")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::ArrayQueue;
+    use std::old_io::timer::sleep;
+    use std::time::Duration;
+    use std::thread::Thread;
+    use std::sync::mpsc::sync_channel;
+
+    #[test]
+    pub fn test_single_threaded_put_take() {
+        let q = ArrayQueue::with_capacity(4);
+
+        assert_eq!(0, q.len());
+        assert_eq!(4, q.capacity());
+
+        q.put(1u);
+        assert_eq!(1, q.len());
+
+        assert_eq!(1u, q.take());
+        assert_eq!(0, q.len());
+
+        assert!(q.poll().is_none());
+    }
+
+    #[test]
+    pub fn test_wrap_around() {
+        let q = ArrayQueue::with_capacity(3);
+
+        for i in range(0, 100u) {
+            assert!(q.offer(2 * i).is_ok());
+            assert!(q.offer(2 * i + 1).is_ok());
+
+            assert_eq!(Some(2 * i), q.poll());
+            assert_eq!(Some(2 * i + 1), q.poll());
+            assert!(q.poll().is_none());
+        }
+    }
+
+    #[test]
+    pub fn test_offer_at_capacity() {
+        let q = ArrayQueue::with_capacity(2);
+
+        assert!(q.offer(1u).is_ok());
+        assert!(q.offer(2u).is_ok());
+        assert_eq!(Err(3), q.offer(3u));
+
+        assert_eq!(Some(1), q.poll());
+        assert!(q.offer(3u).is_ok());
+
+        assert_eq!(Some(2), q.poll());
+        assert_eq!(Some(3), q.poll());
+    }
+
+    #[test]
+    pub fn test_put_blocks_when_full() {
+        let q = ArrayQueue::with_capacity(1);
+        let p = q.clone();
+        let (tx, rx) = sync_channel(0);
+
+        q.put(1u);
+
+        Thread::spawn(move || {
+            p.put(2u);
+            tx.send(()).unwrap();
+        });
+
+        sleep(millis(20));
+        assert!(rx.try_recv().is_err());
+
+        assert_eq!(1, q.take());
+        rx.recv().unwrap();
+        assert_eq!(2, q.take());
+    }
+
+    #[test]
+    pub fn test_take_blocks_when_empty() {
+        let q = ArrayQueue::with_capacity(1);
+        let p = q.clone();
+
+        Thread::spawn(move || {
+            sleep(millis(20));
+            p.put(1u);
+        });
+
+        assert_eq!(1, q.take());
+    }
+
+    #[test]
+    pub fn test_single_consumer_single_producer() {
+        let c = ArrayQueue::with_capacity(8);
+        let p = c.clone();
+
+        Thread::spawn(move || {
+            for i in range(0, 10_000u) {
+                p.put(i);
+            }
+        });
+
+        for i in range(0, 10_000) {
+            assert_eq!(i, c.take());
+        }
+
+        assert!(c.poll().is_none());
+    }
+
+    fn millis(num: uint) -> Duration {
+        Duration::milliseconds(num as i64)
+    }
+}
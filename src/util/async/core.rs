@@ -7,7 +7,9 @@ use std::{fmt, mem, ptr};
 use std::num::FromPrimitive;
 use std::sync::atomic::Ordering::{Acquire, Relaxed, Release};
 use std::thread::Thread;
+use std::time::Duration;
 use alloc::heap;
+use time::SteadyTime;
 
 /*
  *
@@ -41,6 +43,10 @@ impl<A: Async + FromCore> Core<A> {
         self.inner().consumer_is_err()
     }
 
+    pub fn consumer_is_canceled(&self) -> bool {
+        self.inner().consumer_is_canceled()
+    }
+
     /// Returns the underlying value if it has been realized, None otherwise.
     pub fn consumer_poll(&self) -> Option<AsyncResult<A::Value, A::Error>> {
         self.inner().consumer_poll()
@@ -65,6 +71,49 @@ impl<A: Async + FromCore> Core<A> {
         self.consumer_poll().expect("result not ready")
     }
 
+    /// Blocks the thread until calling `consumer_poll` will return a value or
+    /// the timeout elapses, in which case None is returned.
+    pub fn consumer_await_timeout(&self, dur: Duration) -> Option<AsyncResult<A::Value, A::Error>> {
+        debug!("Core::consumer_await_timeout; dur={:?}", dur);
+
+        // Ensure not already consuming
+        if self.inner().state.load(Relaxed).is_invoking_consumer() {
+            panic!("cannot block thread when in a callback");
+        }
+
+        let deadline = SteadyTime::now() + dur;
+        let th = Thread::current();
+        let count = self.inner().consumer_ready(move |_| th.unpark());
+
+        while !self.consumer_is_ready() {
+            let now = SteadyTime::now();
+
+            if now >= deadline {
+                // Attempt to withdraw the wakeup callback. If this fails, the
+                // callback has already been invoked, which means that the
+                // value was provided right at the deadline and wins.
+                if let Some(count) = count {
+                    if self.consumer_ready_cancel(count) {
+                        debug!("  - timed out");
+                        return None;
+                    }
+                }
+
+                while !self.consumer_is_ready() {
+                    Thread::park();
+                }
+
+                break;
+            }
+
+            // Spurious wakeups re-enter the loop and only wait for the time
+            // remaining until the deadline.
+            Thread::park_timeout(deadline - now);
+        }
+
+        Some(self.consumer_poll().expect("result not ready"))
+    }
+
     /// Registers a callback that will be invoked when calling `consumer_poll`
     /// will return a value.
     pub fn consumer_ready<F: FnOnce(A) + Send>(&self, f: F) -> Option<u64> {
@@ -79,10 +128,19 @@ impl<A: Async + FromCore> Core<A> {
         self.inner().producer_is_ready()
     }
 
+    /// Returns true if a value has been provided but not consumed yet.
+    pub fn producer_is_pending(&self) -> bool {
+        self.inner().producer_is_pending()
+    }
+
     pub fn producer_is_err(&self) -> bool {
         self.inner().producer_is_err()
     }
 
+    pub fn producer_is_canceled(&self) -> bool {
+        self.inner().producer_is_canceled()
+    }
+
     pub fn producer_poll(&self) -> Option<AsyncResult<A::Producer, ()>> {
         self.inner().producer_poll()
     }
@@ -291,7 +349,9 @@ impl<A: Async + FromCore> CoreInner<A> {
     }
 
     pub fn consumer_is_ready(&self) -> bool {
-        self.state.load(Relaxed).is_ready()
+        // Acquire so that the value is visible once the state is observed
+        // as ready
+        self.state.load(Acquire).is_ready()
     }
 
     pub fn consumer_is_err(&self) -> bool {
@@ -302,6 +362,17 @@ impl<A: Async + FromCore> CoreInner<A> {
         self.val.is_err()
     }
 
+    pub fn consumer_is_canceled(&self) -> bool {
+        if !self.state.load(Acquire).is_ready() {
+            return false;
+        }
+
+        match self.val {
+            Err(ref e) => e.is_cancellation(),
+            Ok(_) => false,
+        }
+    }
+
     pub fn consumer_poll(&self) -> Option<AsyncResult<A::Value, A::Error>> {
         let curr = self.state.load(Relaxed);
 
@@ -459,7 +530,7 @@ impl<A: Async + FromCore> CoreInner<A> {
     }
 
     fn consumer_ready_cancel(&self, count: u64) -> bool {
-        let curr = self.state.load(Relaxed);
+        let mut curr = self.state.load(Relaxed);
 
         debug!("Core::consumer_ready_cancel; count={}; state={:?}", count, curr);
 
@@ -487,6 +558,8 @@ impl<A: Async + FromCore> CoreInner<A> {
                 debug!("  - transitioned from {:?} to {:?}", curr, next);
                 return true;
             }
+
+            curr = actual;
         }
     }
 
@@ -495,10 +568,21 @@ impl<A: Async + FromCore> CoreInner<A> {
         curr.is_producer_ready()
     }
 
+    fn producer_is_pending(&self) -> bool {
+        match self.state.load(Relaxed).lifecycle() {
+            Ready | ReadyProducerWait | ConsumerNotify => true,
+            _ => false,
+        }
+    }
+
     fn producer_is_err(&self) -> bool {
         unimplemented!();
     }
 
+    fn producer_is_canceled(&self) -> bool {
+        self.state.load(Relaxed).is_canceled()
+    }
+
     pub fn producer_poll(&self) -> Option<AsyncResult<A::Producer, ()>> {
         let curr = self.state.load(Relaxed);
 
@@ -1043,11 +1127,27 @@ enum Lifecycle {
 
 #[cfg(test)]
 mod test {
-    use super::State;
+    use super::{Core, State};
+    use util::async::Future;
     use std::mem;
+    use std::sync::atomic::Ordering::Relaxed;
 
     #[test]
     pub fn test_struct_sizes() {
         assert_eq!(mem::size_of::<State>(), mem::size_of::<uint>());
     }
+
+    #[test]
+    pub fn test_clone_tracks_ref_count() {
+        let core: Core<Future<uint, ()>> = Core::with_value(Ok(123));
+        assert_eq!(1, core.inner().refs.load(Relaxed));
+
+        let clone = core.clone();
+        assert_eq!(2, clone.inner().refs.load(Relaxed));
+
+        // The survivor keeps the core alive
+        drop(core);
+        assert_eq!(1, clone.inner().refs.load(Relaxed));
+        assert_eq!(123, clone.consumer_poll().unwrap().unwrap());
+    }
 }
@@ -1,7 +1,9 @@
 use super::{Async, Stream, Cancel, AsyncResult, AsyncError};
-use super::stream;
+use super::{stream, timer};
 use super::core::{Core, OptionCore, FromCore};
+use util::LinkedQueue;
 use std::fmt;
+use std::time::Duration;
 
 /* TODO:
  * - Add AsyncVal trait that impls all the various monadic fns
@@ -43,7 +45,9 @@ impl<T: Send, E: Send> Future<T, E> {
     /// Future::error("hi").or_else(|err| {
     ///     match err {
     ///         ExecutionError(e) => assert!(e == "hi"),
-    ///         CancellationError => unreachable!()
+    ///         CancellationError => unreachable!(),
+    ///         TimeoutError => unreachable!(),
+    ///         PanicError => unreachable!()
     ///     }
     ///
     ///     Ok(())
@@ -63,7 +67,9 @@ impl<T: Send, E: Send> Future<T, E> {
     /// Future::<&'static str, ()>::canceled().or_else(|err| {
     ///     match err {
     ///         ExecutionError(e) => unreachable!(),
-    ///         CancellationError => assert!(true)
+    ///         CancellationError => assert!(true),
+    ///         TimeoutError => unreachable!(),
+    ///         PanicError => unreachable!()
     ///     }
     ///
     ///     Ok("handled")
@@ -74,6 +80,54 @@ impl<T: Send, E: Send> Future<T, E> {
         Future { core: OptionCore::new(core) }
     }
 
+    /// Returns a future that is immediately realized with the supplied result,
+    /// which may be any `AsyncError`, including a cancellation or a timeout.
+    pub fn from_result(res: AsyncResult<T, E>) -> Future<T, E> {
+        Future { core: OptionCore::new(Core::with_value(res)) }
+    }
+
+    /// Returns a future that completes with the first result returned by
+    /// `f`. Once interest in the value is registered, `f` is polled right
+    /// away, then on the shared timer until it returns `Some`, backing off
+    /// between polls. Polling stops if the consumer cancels the future.
+    ///
+    /// This is a low level escape hatch for wrapping async sources that do
+    /// not provide a way to be notified of completion.
+    pub fn from_poll<F>(f: F) -> Future<T, E>
+            where F: FnMut() -> Option<AsyncResult<T, E>> + Send {
+        let (ret, complete) = Future::pair();
+
+        complete.receive(move |c| {
+            if let Ok(complete) = c {
+                poll(f, complete, Backoff::new());
+            }
+        });
+
+        ret
+    }
+
+    /// Returns a future that completes with the next value taken from the
+    /// queue. Once interest in the value is registered, the queue is polled
+    /// the same way as with `from_poll`, so the caller is never blocked. If
+    /// the queue is closed before a value is available, the future is
+    /// canceled.
+    ///
+    /// A value is only taken while the consumer is interested in it, so
+    /// canceling the future leaves the next value in the queue.
+    pub fn from_queue(queue: LinkedQueue<T>) -> Future<T, E> {
+        Future::from_poll(move || {
+            // Check before polling so that a value put right before the
+            // queue is closed is still taken
+            let closed = queue.is_closed();
+
+            match queue.poll() {
+                Some(v) => Some(Ok(v)),
+                None if closed => Some(Err(AsyncError::canceled())),
+                None => None,
+            }
+        })
+    }
+
     /// Returns a future that won't kick off its async action until
     /// a consumer registers interest.
     ///
@@ -105,18 +159,15 @@ impl<T: Send, E: Send> Future<T, E> {
         // interest has been registered.
         complete.receive(move |c: AsyncResult<Complete<T, E>, ()>| {
             if let Ok(c) = c {
-                f().receive(move |res| {
-                    match res {
-                        Ok(v) => c.complete(v),
-                        Err(_) => unimplemented!(),
-                    }
-                });
+                f().receive(move |res| c.resolve(res));
             }
         });
 
         future
     }
 
+    /// Returns true if the future has been realized, in which case `poll`
+    /// will return the result. Does not block or consume the future.
     pub fn is_ready(&self) -> bool {
         self.core.get().consumer_is_ready()
     }
@@ -125,6 +176,14 @@ impl<T: Send, E: Send> Future<T, E> {
         self.core.get().consumer_is_err()
     }
 
+    /// Returns true if the future has been realized with a cancellation
+    /// error. Does not block or consume the future.
+    pub fn is_canceled(&self) -> bool {
+        self.core.get().consumer_is_canceled()
+    }
+
+    /// Takes the result of the future without blocking. If the future has
+    /// not been realized yet, it is returned back in the `Err` variant.
     pub fn poll(mut self) -> Result<AsyncResult<T, E>, Future<T, E>> {
         let core = self.core.take();
 
@@ -147,23 +206,86 @@ impl<T: Send, E: Send> Future<T, E> {
         }
     }
 
-    pub fn receive<F>(self, f: F)
+    pub fn receive<F>(self, f: F) -> CancelFuture<T, E>
             where F: FnOnce(AsyncResult<T, E>) + Send {
-        Async::receive(self, f);
+        Async::receive(self, f)
     }
 
     pub fn await(mut self) -> AsyncResult<T, E> {
         self.core.take().consumer_await()
     }
 
+    /// Blocks the thread until the future is realized or the timeout elapses.
+    /// If the timeout elapses first, the future is canceled and a
+    /// `TimeoutError` is returned.
+    pub fn await_timeout(mut self, dur: Duration) -> AsyncResult<T, E> {
+        let core = self.core.take();
+
+        match core.consumer_await_timeout(dur) {
+            Some(res) => res,
+            None => {
+                core.cancel();
+                Err(AsyncError::timed_out())
+            }
+        }
+    }
+
+    /// Withdraw interest in the future's value. A producer waiting via
+    /// `receive` or `await` will be notified with a `CancellationError`. If
+    /// the value has already been provided, it is dropped.
+    pub fn cancel(mut self) {
+        self.core.take().cancel();
+    }
+
     /*
      *
      * ===== Computation Builders =====
      *
      */
 
-    pub fn map<F: FnOnce(T) -> U + Send, U: Send>(self, _f: F) -> Future<U, E> {
-        unimplemented!();
+    /// Returns a future that completes with the value of `self` transformed
+    /// by `f`. Errors are propagated without invoking `f`.
+    pub fn map<F: FnOnce(T) -> U + Send, U: Send>(self, f: F) -> Future<U, E> {
+        self.and_then(move |v| Ok(f(v)))
+    }
+
+    /// Returns a future that completes with the value of `self` transformed
+    /// by `f`, where the transformation itself may fail. If `f` returns an
+    /// error, the returned future fails with it. Errors from `self` are
+    /// propagated without invoking `f`.
+    pub fn map_result<F, U>(self, f: F) -> Future<U, E>
+            where F: FnOnce(T) -> Result<U, E> + Send,
+                  U: Send {
+        self.and_then(move |v| f(v).map_err(AsyncError::wrap))
+    }
+
+    /// Returns a future that completes with the result returned by `f`,
+    /// which receives the result of `self` whether it succeeded, failed or
+    /// was canceled. This allows transformations that depend on the outcome,
+    /// such as turning a cancellation into a fallback value.
+    pub fn map_both<F, U>(self, f: F) -> Future<U, E>
+            where F: FnOnce(AsyncResult<T, E>) -> AsyncResult<U, E> + Send,
+                  U: Send {
+        self.handle(move |res| f(res))
+    }
+}
+
+impl<E: Send> Future<(), E> {
+    /// Returns a future that completes once `dur` has elapsed. The delay
+    /// starts when interest in the value is registered, so a future that is
+    /// dropped before then never schedules a timer entry.
+    ///
+    /// All delays are tracked by a single shared timer thread.
+    pub fn delay(dur: Duration) -> Future<(), E> {
+        let (ret, complete) = Future::pair();
+
+        complete.receive(move |c| {
+            if let Ok(complete) = c {
+                timer::delay(dur, complete);
+            }
+        });
+
+        ret
     }
 }
 
@@ -274,6 +396,8 @@ impl<T: Send, E: Send> Cancel<Future<T, E>> for CancelFuture<T, E> {
 /// future.or_else(|err| {
 ///     match err {
 ///         CancellationError => unreachable!(),
+///         TimeoutError => unreachable!(),
+///         PanicError => unreachable!(),
 ///         ExecutionError(err) => assert!(err == "failed")
 ///     }
 ///
@@ -297,6 +421,28 @@ impl<T: Send, E: Send> Complete<T, E> {
         self.core.take().complete(Err(AsyncError::wrap(err)), true);
     }
 
+    /// Realizes the associated promise with the supplied result. Unlike
+    /// `fail`, any `AsyncError` can be passed, so combinators use this to
+    /// forward cancellations and timeouts as is.
+    pub fn resolve(mut self, res: AsyncResult<T, E>) {
+        self.core.take().complete(res, true);
+    }
+
+    /// Completes the associated promise with the result of `async` once it is
+    /// realized. Interest in `async` is only registered once the consumer
+    /// has registered interest in the value. If the consumer cancels the
+    /// future, `async` is dropped, which cancels it as well.
+    pub fn complete_with<A: Async<Value=T, Error=E>>(self, async: A) {
+        self.receive(move |c| {
+            if let Ok(complete) = c {
+                async.receive(move |res| complete.resolve(res));
+            }
+        });
+    }
+
+    /// Returns true if the consumer has registered interest in the value, or
+    /// has canceled the future. Producers may use this in order to avoid
+    /// computing a value that nobody is waiting on.
     pub fn is_ready(&self) -> bool {
         self.core.get().producer_is_ready()
     }
@@ -305,6 +451,12 @@ impl<T: Send, E: Send> Complete<T, E> {
         self.core.get().producer_is_err()
     }
 
+    /// Returns true if the consumer has canceled the future, in which case
+    /// any value provided would be discarded.
+    pub fn is_canceled(&self) -> bool {
+        self.core.get().producer_is_canceled()
+    }
+
     fn poll(mut self) -> Result<AsyncResult<Complete<T, E>, ()>, Complete<T, E>> {
         debug!("Complete::poll; is_ready={}", self.is_ready());
 
@@ -371,6 +523,52 @@ impl<T: Send, E: Send> Drop for Complete<T, E> {
     }
 }
 
+// Polls `f` until it returns a result, scheduling each retry on the shared
+// timer instead of blocking a thread.
+fn poll<T, E, F>(mut f: F, complete: Complete<T, E>, mut backoff: Backoff)
+        where T: Send,
+              E: Send,
+              F: FnMut() -> Option<AsyncResult<T, E>> + Send {
+
+    if complete.is_canceled() {
+        debug!("from_poll; future canceled, stop polling");
+        return;
+    }
+
+    match f() {
+        Some(res) => complete.resolve(res),
+        None => {
+            Future::<(), ()>::delay(backoff.next()).receive(move |_| {
+                poll(f, complete, backoff);
+            });
+        }
+    }
+}
+
+// Waits exponentially longer between polls, up to a maximum.
+struct Backoff {
+    delay_ms: i64,
+}
+
+impl Backoff {
+    fn new() -> Backoff {
+        Backoff { delay_ms: 1 }
+    }
+
+    // Returns how long to wait before the next poll
+    fn next(&mut self) -> Duration {
+        let dur = Duration::milliseconds(self.delay_ms);
+
+        if self.delay_ms < MAX_DELAY_MS {
+            self.delay_ms *= 2;
+        }
+
+        dur
+    }
+}
+
+const MAX_DELAY_MS: i64 = 64;
+
 impl<T: Send, E: Send> fmt::Debug for Complete<T, E> {
     fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
         write!(fmt, "Complete {{ ... }}")
@@ -1,4 +1,4 @@
-use super::{Async, Future, Complete, AsyncError};
+use super::{Async, Future, Complete, AsyncResult, AsyncError};
 use std::cell::UnsafeCell;
 use std::sync::Arc;
 use std::sync::atomic::{self, AtomicInt};
@@ -18,6 +18,44 @@ pub fn join<J: Join<T, E>, T: Send, E: Send>(asyncs: J) -> Future<T, E> {
     future
 }
 
+/// Returns a future that completes with the values of the three async values
+/// once all of them are realized, or fails as soon as one of them fails.
+///
+/// The values are built from nested pairwise joins and flattened.
+pub fn join3<A1, A2, A3, E>(a1: A1, a2: A2, a3: A3) -> Future<(A1::Value, A2::Value, A3::Value), E>
+        where A1: Async<Error=E>,
+              A2: Async<Error=E>,
+              A3: Async<Error=E>,
+              E: Send {
+
+    join((join((a1, a2)), a3))
+        .map(move |((v1, v2), v3)| (v1, v2, v3))
+}
+
+/// Returns a future that completes with the values of the four async values
+/// once all of them are realized, or fails as soon as one of them fails.
+pub fn join4<A1, A2, A3, A4, E>(a1: A1, a2: A2, a3: A3, a4: A4) -> Future<(A1::Value, A2::Value, A3::Value, A4::Value), E>
+        where A1: Async<Error=E>,
+              A2: Async<Error=E>,
+              A3: Async<Error=E>,
+              A4: Async<Error=E>,
+              E: Send {
+
+    join((join((a1, a2)), join((a3, a4))))
+        .map(move |((v1, v2), (v3, v4))| (v1, v2, v3, v4))
+}
+
+/// Returns a future that completes with the result of every async value once
+/// all of them are realized, in the same order as `asyncs`. Unlike `join`,
+/// failures do not short-circuit, so the returned future never fails.
+pub fn all<A: Async<Error=E>, E: Send>(asyncs: Vec<A>) -> Future<Vec<AsyncResult<A::Value, E>>, E> {
+    let settled: Vec<Future<AsyncResult<A::Value, E>, E>> = asyncs.into_iter()
+        .map(|async| async.handle(move |res| Future::of(res)))
+        .collect();
+
+    join(settled)
+}
+
 pub trait Join<T, E> : Send {
     fn join(self, complete: Complete<T, E>);
 }
@@ -38,6 +76,12 @@ impl<T1, T2, T3> Partial<(T1, T2, T3)> for (Option<T1>, Option<T2>, Option<T3>)
     }
 }
 
+impl<T> Partial<Vec<T>> for Vec<Option<T>> {
+    fn consume(&mut self) -> Vec<T> {
+        self.iter_mut().map(|v| v.take().unwrap()).collect()
+    }
+}
+
 struct Progress<P: Partial<R>, R: Send, E: Send> {
     inner: Arc<UnsafeCell<ProgressInner<P, R, E>>>,
 }
@@ -68,12 +112,7 @@ impl<P: Partial<R>, R: Send, E: Send> Progress<P, R, E> {
             let complete = self.inner_mut().complete.take()
                 .expect("complete already consumed");
 
-            // If not an execution error, it is a cancellation error, in which
-            // case, our complete will go out of scope and propagate up a
-            // cancellation.
-            if let AsyncError::ExecutionError(e) = err {
-                complete.fail(e);
-            }
+            complete.resolve(Err(err));
         }
     }
 
@@ -182,3 +221,50 @@ impl<A1: Async<Error=E>, A2: Async<Error=E>, A3: Async<Error=E>, E> Join<(A1::Va
         component!(a3, p, 2);
     }
 }
+
+/*
+ *
+ * ===== Join for Vec =====
+ *
+ */
+
+impl<A: Async<Error=E>, E> Join<Vec<A::Value>, E> for Vec<A>
+        where E: Send,
+              A::Value: Send {
+
+    fn join(self, complete: Complete<Vec<A::Value>, E>) {
+        let len = self.len();
+
+        if len == 0 {
+            complete.complete(vec![]);
+            return;
+        }
+
+        let vals = range(0, len).map(|_| None).collect();
+        let p = Progress::new(vals, complete, len as isize);
+
+        for (i, async) in self.into_iter().enumerate() {
+            let p = p.clone();
+
+            async.receive(move |res| {
+                debug!("dependent future complete; id={}; success={}", i, res.is_ok());
+
+                match res {
+                    Ok(v) => {
+                        // Each async value has its own slot in the staging
+                        // area, so slots are never written concurrently
+                        p.vals_mut()[i] = Some(v);
+
+                        if p.dec() == 0 {
+                            debug!("last future completed -- completing join");
+                            p.succeed();
+                        }
+                    }
+                    Err(e) => {
+                        p.fail(e);
+                    }
+                }
+            });
+        }
+    }
+}
@@ -23,13 +23,16 @@
 
 pub use self::future::{Future, Complete};
 pub use self::stream::{Stream, StreamIter, Generate};
-pub use self::join::{join, Join};
+pub use self::join::{all, join, join3, join4, Join};
+pub use self::retry::{retry, retry_with_backoff};
 pub use self::select::{select, Select};
+pub use self::token::{CancelSource, CancelToken};
 
 use util::Run;
 
 use std::fmt;
-use self::AsyncError::*;
+use std::sync::mpsc::Sender;
+use std::time::Duration;
 
 // ## TODO
 //
@@ -43,8 +46,13 @@ use self::AsyncError::*;
 mod core;
 mod future;
 mod join;
+mod race;
+mod retry;
 mod select;
 mod stream;
+mod timeout;
+mod timer;
+mod token;
 
 pub trait Async : Send + Sized {
     type Value: Send;
@@ -73,15 +81,18 @@ pub trait Async : Send + Sized {
     /// consumed.
     fn ready<F>(self, f: F) -> Self::Cancel where F: FnOnce(Self) + Send;
 
-    /// Invoke the callback with the resolved `Async` result.
-    fn receive<F>(self, f: F)
+    /// Invoke the callback with the resolved `Async` result. Returns a token
+    /// that can be used to withdraw the callback before it is invoked, in
+    /// which case the `Async` instance is handed back. Canceling a callback
+    /// that already fired is a no-op.
+    fn receive<F>(self, f: F) -> Self::Cancel
             where F: FnOnce(AsyncResult<Self::Value, Self::Error>) + Send {
         self.ready(move |async| {
             match async.poll() {
                 Ok(res) => f(res),
                 Err(_) => panic!("ready callback invoked but is not actually ready"),
             }
-        });
+        })
     }
 
     /// Invoke the callback on the specified `Run` with the resolved `Async`
@@ -96,6 +107,14 @@ pub trait Async : Send + Sized {
         });
     }
 
+    /// Sends the resolved `Async` result on the channel. If the receiving
+    /// end has hung up, the result is dropped.
+    fn into_sender(self, tx: Sender<AsyncResult<Self::Value, Self::Error>>) {
+        self.receive(move |res| {
+            let _ = tx.send(res);
+        });
+    }
+
     fn await(self) -> AsyncResult<Self::Value, Self::Error> {
         use std::sync::mpsc::channel;
 
@@ -122,16 +141,11 @@ pub trait Async : Send + Sized {
         complete.receive(move |c| {
             if let Ok(complete) = c {
                 self.receive(move |v| {
-                    cb(v).receive(move |res| {
-                        match res {
-                            Ok(u) => complete.complete(u),
-                            Err(e) => {
-                                if let AsyncError::ExecutionError(e) = e {
-                                    complete.fail(e);
-                                }
-                            }
-                        }
-                    });
+                    let mut guard = FailOnPanic::new(complete);
+                    let next = cb(v);
+                    let complete = guard.disarm();
+
+                    next.receive(move |res| complete.resolve(res));
                 });
             }
         });
@@ -140,12 +154,19 @@ pub trait Async : Send + Sized {
     }
 
     /// If the future completes successfully, returns the complection of
-    /// `next`.
+    /// `next`. If the future fails, `next` is dropped without being driven
+    /// and the returned future fails with the same error.
     fn and<U: Async<Error=Self::Error>>(self, next: U) -> Future<U::Value, Self::Error> {
         self.and_then(move |_| next)
     }
 
-    /// Also handles the Future::map case
+    /// If the future completes successfully, returns the completion of the
+    /// async value returned by `f`. If the future fails, `f` is not invoked
+    /// and the returned future fails with the same error.
+    ///
+    /// `f` is invoked on the thread that realizes `self`. If `f` panics, the
+    /// returned future fails with a `PanicError` and the panic continues to
+    /// unwind that thread.
     fn and_then<F, U: Async<Error=Self::Error>>(self, f: F) -> Future<U::Value, Self::Error>
             where F: FnOnce(Self::Value) -> U + Send,
                   U::Value: Send {
@@ -156,16 +177,44 @@ pub trait Async : Send + Sized {
                 self.receive(move |res| {
                     match res {
                         Ok(v) => {
-                            f(v).receive(move |res| {
-                                match res {
-                                    Ok(u) => complete.complete(u),
-                                    Err(ExecutionError(e)) => complete.fail(e),
-                                    _ => {}
-                                }
+                            let mut guard = FailOnPanic::new(complete);
+                            let next = f(v);
+                            let complete = guard.disarm();
+
+                            next.receive(move |res| complete.resolve(res));
+                        }
+                        Err(e) => complete.resolve(Err(e)),
+                    }
+                });
+            }
+        });
+
+        ret
+    }
+
+    /// Like `and_then`, but `f` is invoked on `run` instead of on the thread
+    /// that realizes `self`. This prevents long running continuations from
+    /// blocking the producer's thread.
+    fn and_then_spawn<F, U, R>(self, run: R, f: F) -> Future<U::Value, Self::Error>
+            where F: FnOnce(Self::Value) -> U + Send,
+                  U: Async<Error=Self::Error>,
+                  R: Run {
+        let (ret, complete) = Future::pair();
+
+        complete.receive(move |c| {
+            if let Ok(complete) = c {
+                self.receive(move |res| {
+                    match res {
+                        Ok(v) => {
+                            run.run(move || {
+                                let mut guard = FailOnPanic::new(complete);
+                                let next = f(v);
+                                let complete = guard.disarm();
+
+                                next.receive(move |res| complete.resolve(res));
                             });
                         }
-                        Err(ExecutionError(e)) => complete.fail(e),
-                        _ => {}
+                        Err(e) => complete.resolve(Err(e)),
                     }
                 });
             }
@@ -174,11 +223,94 @@ pub trait Async : Send + Sized {
         ret
     }
 
+    /// Returns a future that completes with the same result as `self`, after
+    /// first invoking `f` with a reference to the result. `f` is invoked
+    /// exactly once, before the downstream consumer is notified.
+    fn inspect<F>(self, f: F) -> Future<Self::Value, Self::Error>
+            where F: FnOnce(&AsyncResult<Self::Value, Self::Error>) + Send {
+
+        let (ret, complete) = Future::pair();
+
+        complete.receive(move |c| {
+            if let Ok(complete) = c {
+                self.receive(move |res| {
+                    f(&res);
+                    complete.resolve(res);
+                });
+            }
+        });
+
+        ret
+    }
+
+    /// Returns a future that completes with the values of both `self` and
+    /// `other` once both are realized. The returned future fails as soon as
+    /// either of them fails. This is equivalent to `join((self, other))`.
+    fn join<U: Async<Error=Self::Error>>(self, other: U) -> Future<(Self::Value, U::Value), Self::Error> {
+        join::join((self, other))
+    }
+
+    /// Returns a future that completes with the value of `self` on success.
+    /// If `self` fails with an execution error, the returned future fails
+    /// with the error returned by `f`. Cancellation is propagated as is.
+    fn map_err<F, U>(self, f: F) -> Future<Self::Value, U>
+            where F: FnOnce(Self::Error) -> U + Send,
+                  U: Send {
+
+        let (ret, complete) = Future::pair();
+
+        complete.receive(move |c| {
+            if let Ok(complete) = c {
+                self.receive(move |res| {
+                    complete.resolve(res.map_err(move |e| e.map(f)));
+                });
+            }
+        });
+
+        ret
+    }
+
+    /// Returns a future that completes with the same result as `self`, but
+    /// the result is delivered on `run` instead of on the thread that
+    /// realizes `self`. Downstream callbacks are then invoked on the executor
+    /// and cannot hold up the producer.
+    fn on_executor<R: Run>(self, run: R) -> Future<Self::Value, Self::Error> {
+        let (ret, complete) = Future::pair();
+
+        complete.receive(move |c| {
+            if let Ok(complete) = c {
+                self.receive(move |res| {
+                    run.run(move || complete.resolve(res));
+                });
+            }
+        });
+
+        ret
+    }
+
+    /// Returns a future that completes with the value of whichever of `self`
+    /// or `alt` succeeds first. If the first to complete fails, the other one
+    /// is waited on. The returned future only fails if both fail. Once a
+    /// winner is picked, the other async value is canceled.
     fn or<A>(self, alt: A) -> Future<Self::Value, Self::Error>
             where A: Async<Value=Self::Value, Error=Self::Error> {
-        self.or_else(move |_| alt)
+
+        let (ret, complete) = Future::pair();
+
+        complete.receive(move |c| {
+            if let Ok(complete) = c {
+                race::race(self, alt, complete);
+            }
+        });
+
+        ret
     }
 
+    /// Returns a future that completes with the value of `self` on success.
+    /// If `self` fails, `f` is invoked with the error and the returned future
+    /// completes with the result of the async value returned by `f`. Since
+    /// `AsyncResult` is itself async, recovering with a plain value is done
+    /// by returning `Ok(val)`.
     fn or_else<F, A>(self, f: F) -> Future<Self::Value, Self::Error>
             where F: FnOnce(AsyncError<Self::Error>) -> A + Send,
                   A: Async<Value=Self::Value, Error=Self::Error> {
@@ -191,13 +323,11 @@ pub trait Async : Send + Sized {
                     match res {
                         Ok(v) => complete.complete(v),
                         Err(e) => {
-                            f(e).receive(move |res| {
-                                match res {
-                                    Ok(v) => complete.complete(v),
-                                    Err(ExecutionError(e)) => complete.fail(e),
-                                    _ => {}
-                                }
-                            });
+                            let mut guard = FailOnPanic::new(complete);
+                            let next = f(e);
+                            let complete = guard.disarm();
+
+                            next.receive(move |res| complete.resolve(res));
                         }
                     }
                 });
@@ -206,6 +336,57 @@ pub trait Async : Send + Sized {
 
         ret
     }
+
+    /// Returns a future that completes with the result of `self` if it is
+    /// realized before `dur` elapses. Otherwise, `self` is canceled and the
+    /// returned future fails with a `TimeoutError`.
+    fn timeout(self, dur: Duration) -> Future<Self::Value, Self::Error> {
+        let (ret, complete) = Future::pair();
+
+        complete.receive(move |c| {
+            if let Ok(complete) = c {
+                timeout::timeout(self, dur, complete);
+            }
+        });
+
+        ret
+    }
+
+    /// Returns a future that completes with the value of `self` on success,
+    /// or with `default` if `self` fails for any reason, including
+    /// cancellation.
+    fn unwrap_or(self, default: Self::Value) -> Future<Self::Value, Self::Error> {
+        let (ret, complete) = Future::pair();
+
+        complete.receive(move |c| {
+            if let Ok(complete) = c {
+                self.receive(move |res| {
+                    match res {
+                        Ok(v) => complete.complete(v),
+                        Err(_) => complete.complete(default),
+                    }
+                });
+            }
+        });
+
+        ret
+    }
+
+    /// Returns a future that completes with the result of `self`, unless the
+    /// source of `token` is canceled first, in which case the returned future
+    /// is canceled. Binding to a token that is already canceled cancels the
+    /// returned future right away.
+    fn with_token(self, token: CancelToken) -> Future<Self::Value, Self::Error> {
+        let (ret, complete) = Future::pair();
+
+        complete.receive(move |c| {
+            if let Ok(complete) = c {
+                token::with_token(self, token, complete);
+            }
+        });
+
+        ret
+    }
 }
 
 pub trait Cancel<A: Send> : Send {
@@ -289,6 +470,8 @@ pub type AsyncResult<T, E> = Result<T, AsyncError<E>>;
 pub enum AsyncError<E: Send> {
     ExecutionError(E),
     CancellationError,
+    TimeoutError,
+    PanicError,
 }
 
 impl<E: Send> AsyncError<E> {
@@ -300,6 +483,14 @@ impl<E: Send> AsyncError<E> {
         AsyncError::CancellationError
     }
 
+    pub fn timed_out() -> AsyncError<E> {
+        AsyncError::TimeoutError
+    }
+
+    pub fn panicked() -> AsyncError<E> {
+        AsyncError::PanicError
+    }
+
     pub fn is_cancellation(&self) -> bool {
         match *self {
             AsyncError::CancellationError => true,
@@ -314,10 +505,39 @@ impl<E: Send> AsyncError<E> {
         }
     }
 
+    pub fn is_timeout(&self) -> bool {
+        match *self {
+            AsyncError::TimeoutError => true,
+            _ => false,
+        }
+    }
+
+    pub fn is_panic(&self) -> bool {
+        match *self {
+            AsyncError::PanicError => true,
+            _ => false,
+        }
+    }
+
     pub fn unwrap(self) -> E {
         match self {
             AsyncError::ExecutionError(err) => err,
             AsyncError::CancellationError => panic!("unwrapping a cancellation error"),
+            AsyncError::TimeoutError => panic!("unwrapping a timeout error"),
+            AsyncError::PanicError => panic!("unwrapping a panic error"),
+        }
+    }
+
+    /// Maps the error of an `ExecutionError` with `f`, leaving cancellations,
+    /// timeouts and panics as is.
+    pub fn map<F, U>(self, f: F) -> AsyncError<U>
+            where F: FnOnce(E) -> U,
+                  U: Send {
+        match self {
+            AsyncError::ExecutionError(err) => AsyncError::ExecutionError(f(err)),
+            AsyncError::CancellationError => AsyncError::CancellationError,
+            AsyncError::TimeoutError => AsyncError::TimeoutError,
+            AsyncError::PanicError => AsyncError::PanicError,
         }
     }
 
@@ -329,11 +549,56 @@ impl<E: Send> AsyncError<E> {
     }
 }
 
+impl<E: Send + Clone> Clone for AsyncError<E> {
+    fn clone(&self) -> AsyncError<E> {
+        match *self {
+            AsyncError::ExecutionError(ref e) => AsyncError::ExecutionError(e.clone()),
+            AsyncError::CancellationError => AsyncError::CancellationError,
+            AsyncError::TimeoutError => AsyncError::TimeoutError,
+            AsyncError::PanicError => AsyncError::PanicError,
+        }
+    }
+}
+
 impl<E: Send + fmt::Debug> fmt::Debug for AsyncError<E> {
     fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
         match *self {
             AsyncError::ExecutionError(ref e) => write!(fmt, "ExecutionError({:?})", e),
             AsyncError::CancellationError => write!(fmt, "CancellationError"),
+            AsyncError::TimeoutError => write!(fmt, "TimeoutError"),
+            AsyncError::PanicError => write!(fmt, "PanicError"),
+        }
+    }
+}
+
+/*
+ *
+ * ===== FailOnPanic =====
+ *
+ */
+
+// Fails the future with a `PanicError` if it is dropped before being
+// disarmed, which only happens when a continuation panics.
+struct FailOnPanic<T: Send, E: Send> {
+    complete: Option<Complete<T, E>>,
+}
+
+impl<T: Send, E: Send> FailOnPanic<T, E> {
+    fn new(complete: Complete<T, E>) -> FailOnPanic<T, E> {
+        FailOnPanic { complete: Some(complete) }
+    }
+
+    // Returns the complete, the guard no longer fails it
+    fn disarm(&mut self) -> Complete<T, E> {
+        self.complete.take().expect("complete already consumed")
+    }
+}
+
+#[unsafe_destructor]
+impl<T: Send, E: Send> Drop for FailOnPanic<T, E> {
+    fn drop(&mut self) {
+        if let Some(complete) = self.complete.take() {
+            complete.resolve(Err(AsyncError::panicked()));
         }
     }
 }
@@ -0,0 +1,134 @@
+use super::{Async, Complete, Cancel};
+use std::sync::{Arc, Mutex};
+
+/// Completes `complete` with the first of the two async values to succeed.
+/// If both fail, the error of the last one to fail is used. Once a winner has
+/// been picked, the callback registered on the other async value is withdrawn
+/// and the value dropped, canceling it.
+pub fn race<A1, A2>(a1: A1, a2: A2, complete: Complete<A1::Value, A1::Error>)
+        where A1: Async,
+              A2: Async<Value=A1::Value, Error=A1::Error> {
+
+    let race = Race::new(complete);
+
+    // Register interest in the first async value. The callback may be invoked
+    // immediately if the value is already realized.
+    let r = race.clone();
+    let c1 = a1.ready(move |a| r.ready(a, 0));
+
+    if !race.track(|s| s.cancel1 = Some(c1)) {
+        // The first async value won, there is no need to register interest
+        // in the second. Dropping it cancels it.
+        debug!("race won before registering second callback");
+        drop(a2);
+        return;
+    }
+
+    let r = race.clone();
+    let c2 = a2.ready(move |a| r.ready(a, 1));
+
+    // If the first async value won while the second callback was being
+    // registered, it could not withdraw the callback. Do so now.
+    let mut c2 = Some(c2);
+
+    if !race.track(|s| s.cancel2 = c2.take()) {
+        if let Some(c2) = c2 {
+            drop(c2.cancel());
+        }
+    }
+}
+
+struct Race<A1: Async, A2: Async<Value=A1::Value, Error=A1::Error>> {
+    state: Arc<Mutex<State<A1, A2>>>,
+}
+
+impl<A1, A2> Race<A1, A2>
+        where A1: Async,
+              A2: Async<Value=A1::Value, Error=A1::Error> {
+
+    fn new(complete: Complete<A1::Value, A1::Error>) -> Race<A1, A2> {
+        let state = State {
+            complete: Some(complete),
+            cancel1: None,
+            cancel2: None,
+            failed: 0,
+        };
+
+        Race { state: Arc::new(Mutex::new(state)) }
+    }
+
+    // Runs the function with the state if the race has not been decided yet.
+    // Returns false if the race is already decided.
+    fn track<F: FnOnce(&mut State<A1, A2>)>(&self, f: F) -> bool {
+        let mut state = self.state.lock()
+            .ok().expect("something went wrong");
+
+        if state.complete.is_none() {
+            return false;
+        }
+
+        f(&mut *state);
+        true
+    }
+
+    fn ready<A>(&self, async: A, index: u32)
+            where A: Async<Value=A1::Value, Error=A1::Error> {
+
+        let mut cancel1 = None;
+        let mut cancel2 = None;
+        let complete;
+
+        {
+            let mut state = self.state.lock()
+                .ok().expect("something went wrong");
+
+            if state.complete.is_none() {
+                // The race has already been decided, drop the value
+                return;
+            }
+
+            if async.is_err() {
+                state.failed += 1;
+
+                if state.failed < 2 {
+                    debug!("race contender failed; index={}", index);
+                    return;
+                }
+            } else if index == 0 {
+                cancel2 = state.cancel2.take();
+            } else {
+                cancel1 = state.cancel1.take();
+            }
+
+            complete = state.complete.take().expect("complete already consumed");
+        }
+
+        // Withdraw interest in the losing async value. If the callback has
+        // already fired, it will see that the race is decided.
+        if let Some(c) = cancel1 {
+            drop(c.cancel());
+        }
+
+        if let Some(c) = cancel2 {
+            drop(c.cancel());
+        }
+
+        complete.resolve(async.expect());
+    }
+}
+
+impl<A1, A2> Clone for Race<A1, A2>
+        where A1: Async,
+              A2: Async<Value=A1::Value, Error=A1::Error> {
+
+    fn clone(&self) -> Race<A1, A2> {
+        Race { state: self.state.clone() }
+    }
+}
+
+struct State<A1: Async, A2: Async> {
+    complete: Option<Complete<A1::Value, A1::Error>>,
+    cancel1: Option<A1::Cancel>,
+    cancel2: Option<A2::Cancel>,
+    failed: u32,
+}
@@ -0,0 +1,105 @@
+use super::{Async, Future, Complete};
+use super::AsyncError::ExecutionError;
+use std::time::Duration;
+
+/// Returns a future that completes with the result of the async value
+/// returned by `f`. If that value fails with an execution error, `f` is
+/// invoked again, up to `attempts` times in total. Cancellations and
+/// timeouts are not retried.
+pub fn retry<F, A>(f: F, attempts: usize) -> Future<A::Value, A::Error>
+        where F: Fn() -> A + Send,
+              A: Async {
+
+    assert!(attempts > 0, "attempts must be greater than zero");
+
+    let (ret, complete) = Future::pair();
+
+    // Don't do any work until the consumer registers interest in the completed
+    // value.
+    complete.receive(move |res| {
+        if let Ok(complete) = res {
+            attempt(f, attempts, complete);
+        }
+    });
+
+    ret
+}
+
+/// Like `retry`, but waits before each new attempt. The first retry happens
+/// `base` after the failure, and each following wait is `factor` times longer
+/// than the previous one. If the consumer cancels the returned future during
+/// a wait, no further attempt is made.
+pub fn retry_with_backoff<F, A>(f: F, attempts: usize, base: Duration, factor: f64) -> Future<A::Value, A::Error>
+        where F: Fn() -> A + Send,
+              A: Async {
+
+    assert!(attempts > 0, "attempts must be greater than zero");
+
+    let (ret, complete) = Future::pair();
+
+    complete.receive(move |res| {
+        if let Ok(complete) = res {
+            attempt_with_backoff(f, attempts, base, factor, complete);
+        }
+    });
+
+    ret
+}
+
+fn attempt<F, A>(f: F, remaining: usize, complete: Complete<A::Value, A::Error>)
+        where F: Fn() -> A + Send,
+              A: Async {
+
+    f().receive(move |res| {
+        match res {
+            Err(ExecutionError(e)) => {
+                if remaining > 1 {
+                    debug!("retrying failed async value; remaining={}", remaining - 1);
+                    attempt(f, remaining - 1, complete);
+                } else {
+                    complete.fail(e);
+                }
+            }
+            // Successes, cancellations and timeouts are not retried
+            res => complete.resolve(res),
+        }
+    });
+}
+
+fn attempt_with_backoff<F, A>(f: F, remaining: usize, wait: Duration, factor: f64, complete: Complete<A::Value, A::Error>)
+        where F: Fn() -> A + Send,
+              A: Async {
+
+    f().receive(move |res| {
+        match res {
+            Err(ExecutionError(e)) => {
+                if remaining > 1 {
+                    debug!("retrying failed async value; remaining={}; wait={}ms",
+                           remaining - 1, wait.num_milliseconds());
+
+                    let next = scale(wait, factor);
+
+                    Future::<(), ()>::delay(wait).receive(move |_| {
+                        // Only keep going if the consumer is still interested
+                        complete.receive(move |c| {
+                            if let Ok(complete) = c {
+                                attempt_with_backoff(f, remaining - 1, next, factor, complete);
+                            }
+                        });
+                    });
+                } else {
+                    complete.fail(e);
+                }
+            }
+            // Successes, cancellations and timeouts are not retried
+            res => complete.resolve(res),
+        }
+    });
+}
+
+fn scale(dur: Duration, factor: f64) -> Duration {
+    let micros = dur.num_microseconds()
+        .expect("backoff wait overflowed");
+
+    Duration::microseconds((micros as f64 * factor) as i64)
+}
@@ -6,8 +6,13 @@ use std::cell::UnsafeCell;
 use std::sync::Arc;
 use std::{fmt, u32};
 
-use super::AsyncError::ExecutionError;
-
+/// Returns a future that completes with the index of the first async value to
+/// be realized, along with all of the async values.
+///
+/// Interest is registered on the async values in order and the selection is
+/// decided as soon as one of them is found to be realized. This means that if
+/// several of the values are already realized when the consumer registers
+/// interest, the one with the lowest index always wins.
 pub fn select<S: Select<E>, E: Send>(asyncs: S) -> Future<(u32, S), E> {
     let (res, complete) = Future::pair();
 
@@ -105,12 +110,10 @@ impl<V: Values<S, E>, S: Select<E>, E: Send> Selection<V, S, E> {
                 let complete = self.core_mut().complete.take()
                     .expect("result future previously completed");
 
-                if let Err(ExecutionError(e)) = async.expect() {
-                    debug!("execution error");
-                    complete.fail(e);
+                if let Err(e) = async.expect() {
+                    complete.resolve(Err(e));
                 }
 
-                // If cancellation error, don't do anything
                 return;
             }
         }
@@ -223,6 +226,67 @@ struct Core<V: Values<S, E>, S: Select<E>, E: Send> {
  *
  */
 
+/// Selects over async values of different types, evaluating the arm of the
+/// first one to be realized with its value. The returned future completes
+/// with the result of the arm, so all arms must evaluate to the same type.
+/// If the first async value to be realized fails, the returned future fails
+/// with the same error. The other async values are dropped, which cancels
+/// them.
+///
+/// ```
+/// #[macro_use]
+/// extern crate syncbox;
+///
+/// use syncbox::util::async::*;
+///
+/// fn main() {
+///     let (f1, _c1) = Future::<i32, ()>::pair();
+///     let (f2, c2) = Future::<&'static str, ()>::pair();
+///
+///     let f = select! {
+///         v = f1 => v,
+///         s = f2 => s.len() as i32
+///     };
+///
+///     c2.complete("hello");
+///     assert_eq!(5, f.await().unwrap());
+/// }
+/// ```
+#[macro_export]
+macro_rules! select {
+    ($n1:ident = $a1:expr => $b1:expr, $n2:ident = $a2:expr => $b2:expr) => {{
+        use $crate::util::async::Async;
+
+        $crate::util::async::select(($a1, $a2))
+            .and_then(move |(i, (a1, a2))| {
+                match i {
+                    0 => { drop(a2); a1.expect().map(move |$n1| $b1) }
+                    _ => { drop(a1); a2.expect().map(move |$n2| $b2) }
+                }
+            })
+    }};
+
+    ($n1:ident = $a1:expr => $b1:expr,
+     $n2:ident = $a2:expr => $b2:expr,
+     $n3:ident = $a3:expr => $b3:expr) => {{
+        use $crate::util::async::Async;
+
+        $crate::util::async::select(($a1, $a2, $a3))
+            .and_then(move |(i, (a1, a2, a3))| {
+                match i {
+                    0 => { drop((a2, a3)); a1.expect().map(move |$n1| $b1) }
+                    1 => { drop((a1, a3)); a2.expect().map(move |$n2| $b2) }
+                    _ => { drop((a1, a2)); a3.expect().map(move |$n3| $b3) }
+                }
+            })
+    }};
+
+    // Allow a trailing comma
+    ($($n:ident = $a:expr => $b:expr),+,) => {
+        select!($($n = $a => $b),+)
+    };
+}
+
 macro_rules! expr {
     ($e: expr) => { $e };
 }
@@ -553,3 +617,83 @@ impl<A1: Async<Error=E>, A2: Async<Error=E>, A3: Async<Error=E>, E> Values<(A1,
         ret
     }
 }
+
+/*
+ *
+ * ===== Select for Vec =====
+ *
+ */
+
+impl<A: Async<Error=E>, E: Send> Select<E> for Vec<A> {
+    fn select(self, complete: Complete<(u32, Vec<A>), E>) {
+        let len = self.len();
+
+        if len == 0 {
+            // There is nothing to select, dropping the complete propagates a
+            // cancellation
+            debug!("selecting over an empty vec");
+            return;
+        }
+
+        let vals = range(0, len).map(|_| None).collect();
+        let tokens = range(0, len).map(|_| None).collect();
+
+        // Create the selection
+        let selection = Selection::new(vals, tokens, len as u32, complete);
+
+        let mut pending = true;
+        let mut handled = 0;
+
+        for (i, async) in self.into_iter().enumerate() {
+            if pending {
+                let s = selection.clone();
+                let c = async.ready(move |a| {
+                    s.async_ready(a, i as u32, &mut s.core_mut().vals[i])
+                });
+
+                let core = selection.core_mut();
+
+                pending = selection.track_callback(
+                    c, &mut core.vals[i], &mut core.tokens[i]);
+            } else {
+                selection.core_mut().vals[i] = Some(async);
+                handled += 1;
+            }
+        }
+
+        if handled > 0 {
+            selection.dec_remaining(handled, selection.core().state.load(Ordering::Relaxed));
+        }
+    }
+}
+
+impl<A: Async<Error=E>, E: Send> Values<Vec<A>, E> for Vec<Option<A>> {
+    type Tokens = Vec<Option<A::Cancel>>;
+
+    fn consume(&mut self) -> Vec<A> {
+        self.iter_mut().map(|a| a.take().unwrap()).collect()
+    }
+
+    fn cancel_callbacks(&mut self,
+                        selected: u32,
+                        up_to: u32,
+                        tokens: &mut Vec<Option<A::Cancel>>) -> u32 {
+
+        let mut ret = 0;
+
+        for i in range(0, up_to as usize) {
+            if selected as usize == i {
+                continue;
+            }
+
+            let cancel = tokens[i].take().expect("cancel token missing");
+
+            if let Some(async) = cancel.cancel() {
+                self[i] = Some(async);
+                ret += 1;
+            }
+        }
+
+        ret
+    }
+}
@@ -1,6 +1,9 @@
 use util::async::{self, Async, Future, Cancel, AsyncResult, AsyncError};
 use super::core::{Core, OptionCore, FromCore};
+use util::LinkedQueue;
+use std::collections::RingBuf;
 use std::fmt;
+use std::time::Duration;
 
 pub type Head<T, E> = Option<(T, Stream<T, E>)>;
 
@@ -17,6 +20,46 @@ impl<T: Send, E: Send> Stream<T, E> {
         (stream, Generate { core: OptionCore::new(core) })
     }
 
+    /// Returns a stream yielding the values of the iterator. The iterator is
+    /// only advanced when the next value of the stream is requested.
+    ///
+    /// ```
+    /// use syncbox::util::async::*;
+    ///
+    /// let stream = Stream::<u32, ()>::from_iter(vec![1, 2, 3].into_iter());
+    /// let vals: Vec<u32> = stream.iter().collect();
+    ///
+    /// assert_eq!([1, 2, 3].as_slice(), vals.as_slice());
+    /// ```
+    pub fn from_iter<I: Iterator<Item=T> + Send>(mut iter: I) -> Stream<T, E> {
+        Future::lazy(move || {
+            match iter.next() {
+                Some(v) => Ok(Some((v, Stream::from_iter(iter)))),
+                None => Ok(None),
+            }
+        }).as_stream()
+    }
+
+    /// Returns a stream yielding the values taken from the queue. The stream
+    /// is done once the queue is closed and all of its values have been
+    /// taken.
+    ///
+    /// Each value is taken the same way as with `Future::from_queue`, so
+    /// requesting the next value never blocks the requesting thread.
+    pub fn from_queue(queue: LinkedQueue<T>) -> Stream<T, E> {
+        Future::from_poll(move || {
+            // Check before polling so that a value put right before the
+            // queue is closed is still yielded
+            let closed = queue.is_closed();
+
+            match queue.poll() {
+                Some(v) => Some(Ok(Some((v, Stream::from_queue(queue.clone()))))),
+                None if closed => Some(Ok(None)),
+                None => None,
+            }
+        }).as_stream()
+    }
+
     pub fn is_ready(&self) -> bool {
         self.core.get().consumer_is_ready()
     }
@@ -25,6 +68,8 @@ impl<T: Send, E: Send> Stream<T, E> {
         self.core.get().consumer_is_err()
     }
 
+    /// Takes the next value of the stream without blocking. If the value has
+    /// not been sent yet, the stream is returned back in the `Err` variant.
     pub fn poll(mut self) -> Result<AsyncResult<Head<T, E>, E>, Stream<T, E>> {
         let core = self.core.take();
 
@@ -47,6 +92,13 @@ impl<T: Send, E: Send> Stream<T, E> {
         self.core.take().consumer_await()
     }
 
+    /// Returns an iterator that blocks the current thread until each value
+    /// of the stream is realized. The iterator ends when the stream is done.
+    ///
+    /// # Panics
+    ///
+    /// The iterator panics if the stream fails or is canceled. Use `await`
+    /// or `receive` in order to handle stream errors.
     pub fn iter(mut self) -> StreamIter<T, E> {
         StreamIter { core: OptionCore::new(self.core.take()) }
     }
@@ -57,12 +109,97 @@ impl<T: Send, E: Send> Stream<T, E> {
      *
      */
 
-    pub fn each<F: Fn(T) + Send>(self, _f: F) -> Future<(), E> {
-        unimplemented!();
+    /// Returns a stream that only yields a value once no newer value has been
+    /// yielded by `self` for `quiet`. Values superseded within that window
+    /// are dropped. When `self` is done, the pending value is yielded right
+    /// away.
+    pub fn debounce(self, quiet: Duration) -> Stream<T, E> {
+        self.handle(move |res| {
+            match res {
+                Ok(Some((v, rest))) => debounce(v, rest, quiet),
+                Ok(None) => Future::of(None).as_stream(),
+                Err(e) => Future::from_result(Err(e)).as_stream(),
+            }
+        }).as_stream()
+    }
+
+    /// Returns a stream yielding all of the values of `self` followed by all
+    /// of the values of `other`. `other` is not driven until `self` is done.
+    pub fn chain(self, other: Stream<T, E>) -> Stream<T, E> {
+        self.handle(move |res| {
+            match res {
+                Ok(Some((v, rest))) => Future::of(Some((v, rest.chain(other)))).as_stream(),
+                Ok(None) => other,
+                Err(e) => Future::from_result(Err(e)).as_stream(),
+            }
+        }).as_stream()
+    }
+
+    /// Returns a future that completes with all of the values of the stream
+    /// once the stream is done.
+    pub fn collect(self) -> Future<Vec<T>, E> {
+        self.reduce(Vec::new(), move |mut vec, v| {
+            vec.push(v);
+            vec
+        })
+    }
+
+    /// Invokes `f` with each value of the stream. The returned future
+    /// completes once `f` has been invoked with the last value, or fails with
+    /// the stream's error.
+    pub fn each<F: Fn(T) + Send>(self, f: F) -> Future<(), E> {
+        self.reduce((), move |_, v| f(v))
+    }
+
+    /// Returns a stream containing only the values for which `f` returns
+    /// true. The upstream is only driven when the next matching value is
+    /// requested.
+    pub fn filter<F: Fn(&T) -> bool + Send>(self, f: F) -> Stream<T, E> {
+        self.handle(move |res| {
+            match res {
+                Ok(Some((v, rest))) => {
+                    if f(&v) {
+                        Future::of(Some((v, rest.filter(f)))).as_stream()
+                    } else {
+                        rest.filter(f)
+                    }
+                }
+                Ok(None) => Future::of(None).as_stream(),
+                Err(e) => Future::from_result(Err(e)).as_stream(),
+            }
+        }).as_stream()
+    }
+
+    /// Maps each value to a stream and yields all of the values of that
+    /// stream before pulling the next value from the upstream.
+    pub fn flat_map<F, U>(self, f: F) -> Stream<U, E>
+            where F: Fn(T) -> Stream<U, E> + Send,
+                  U: Send {
+        self.handle(move |res| {
+            match res {
+                Ok(Some((v, rest))) => f(v).chain(rest.flat_map(f)),
+                Ok(None) => Future::of(None).as_stream(),
+                Err(e) => Future::from_result(Err(e)).as_stream(),
+            }
+        }).as_stream()
     }
 
-    pub fn filter<F: Fn(&T) -> bool + Send>(self, _f: F) -> Stream<T, E> {
-        unimplemented!();
+    /// Groups runs of consecutive values that share the same key, yielding
+    /// each key along with its values. A group is yielded once a value with
+    /// a different key is received or the stream is done.
+    pub fn group_by<F, K>(self, key: F) -> Stream<(K, Vec<T>), E>
+            where F: Fn(&T) -> K + Send,
+                  K: PartialEq + Send {
+        self.handle(move |res| {
+            match res {
+                Ok(Some((v, rest))) => {
+                    let k = key(&v);
+                    group(k, vec![v], rest, key)
+                }
+                Ok(None) => Future::of(None).as_stream(),
+                Err(e) => Future::from_result(Err(e)).as_stream(),
+            }
+        }).as_stream()
     }
 
     pub fn map<F: Fn(T) -> U + Send, U: Send>(self, f: F) -> Stream<U, E> {
@@ -77,17 +214,59 @@ impl<T: Send, E: Send> Stream<T, E> {
         }).as_stream()
     }
 
+    /// Folds every value of the stream into an accumulator, starting with
+    /// `init`. The returned future completes with the final accumulator once
+    /// the stream is done. If the stream fails, the future fails with the
+    /// same error and the partial accumulator is discarded.
     pub fn reduce<F: Fn(U, T) -> U + Send, U: Send>(self, init: U, f: F) -> Future<U, E> {
         self.handle(move |res| {
             match res {
                 Ok(Some((v, rest))) => rest.reduce(f(init, v), f),
                 Ok(None) => Future::of(init),
-                Err(AsyncError::ExecutionError(e)) => Future::error(e),
-                _ => Future::canceled(),
+                Err(e) => Future::from_result(Err(e)),
             }
         })
     }
 
+    /// Threads the state `init` through the stream, yielding the values
+    /// returned by `f`. The stream ends as soon as `f` returns None, and the
+    /// rest of the upstream is not driven any further.
+    pub fn scan<F, S, U>(self, init: S, f: F) -> Stream<U, E>
+            where F: Fn(&mut S, T) -> Option<U> + Send,
+                  S: Send,
+                  U: Send {
+        let mut state = init;
+
+        self.handle(move |res| {
+            // Map the result
+            res.map(move |head| {
+                // Map the option, ending the stream if `f` returns None
+                head.and_then(move |(v, rest)| {
+                    match f(&mut state, v) {
+                        Some(u) => Some((u, rest.scan(state, f))),
+                        None => None,
+                    }
+                })
+            })
+        }).as_stream()
+    }
+
+    /// Returns a stream that skips the first `n` values. The skipped values
+    /// are pulled from the upstream as soon as the first value is requested.
+    pub fn skip(self, n: u64) -> Stream<T, E> {
+        if n == 0 {
+            self
+        } else {
+            self.handle(move |res| {
+                match res {
+                    Ok(Some((_, rest))) => rest.skip(n - 1),
+                    Ok(None) => Future::of(None).as_stream(),
+                    Err(e) => Future::from_result(Err(e)).as_stream(),
+                }
+            }).as_stream()
+        }
+    }
+
     pub fn take(self, n: u64) -> Stream<T, E> {
         if n == 0 {
             Future::of(None).as_stream()
@@ -104,9 +283,24 @@ impl<T: Send, E: Send> Stream<T, E> {
         }
     }
 
-    pub fn take_while<F>(self, _f: F) -> Stream<T, E>
+    /// Returns a stream that yields values until `f` returns false. The value
+    /// for which `f` returned false is dropped along with the rest of the
+    /// upstream, which is not driven any further.
+    pub fn take_while<F>(self, f: F) -> Stream<T, E>
             where F: Fn(&T) -> bool + Send {
-        unimplemented!();
+        self.handle(move |res| {
+            // Map the result
+            res.map(move |head| {
+                // Map the option, ending the stream if the predicate fails
+                head.and_then(move |(v, rest)| {
+                    if f(&v) {
+                        Some((v, rest.take_while(f)))
+                    } else {
+                        None
+                    }
+                })
+            })
+        }).as_stream()
     }
 
     pub fn take_until<A>(self, cond: A) -> Stream<T, E>
@@ -126,6 +320,203 @@ impl<T: Send, E: Send> Stream<T, E> {
                 }
             }).as_stream()
     }
+
+    /// Returns a stream yielding the values of both streams in the order in
+    /// which they are realized. The merged stream is done once both streams
+    /// are done, and fails as soon as either of them fails.
+    pub fn merge(self, other: Stream<T, E>) -> Stream<T, E> {
+        async::select((self, other))
+            .and_then(move |(i, (s1, s2))| {
+                // The stream that won the selection is ready
+                let (ready, pending) = if i == 0 { (s1, s2) } else { (s2, s1) };
+
+                match ready.expect() {
+                    Ok(Some((v, rest))) => Future::of(Some((v, rest.merge(pending)))).as_stream(),
+                    Ok(None) => pending,
+                    Err(e) => Future::from_result(Err(e)).as_stream(),
+                }
+            }).as_stream()
+    }
+
+    /// Returns a stream of pairs made of one value from each stream. Both
+    /// streams are driven when the next pair is requested, and the zipped
+    /// stream ends as soon as either of them does.
+    pub fn zip<U: Send>(self, other: Stream<U, E>) -> Stream<(T, U), E> {
+        async::join((self, other))
+            .and_then(move |(a, b)| {
+                match (a, b) {
+                    (Some((v1, rest1)), Some((v2, rest2))) => {
+                        Ok(Some(((v1, v2), rest1.zip(rest2))))
+                    }
+                    _ => Ok(None),
+                }
+            }).as_stream()
+    }
+}
+
+impl<T: Send + Clone, E: Send> Stream<T, E> {
+    /// Returns a future that completes with a copy of the next value of the
+    /// stream, or None if the stream is done, along with a stream that still
+    /// yields that value followed by the rest of `self`.
+    ///
+    /// ```
+    /// use syncbox::util::async::*;
+    ///
+    /// let stream = Stream::<u32, ()>::from_iter(vec![1, 2, 3].into_iter());
+    /// let (head, stream) = stream.peek().await().unwrap();
+    /// let vals: Vec<u32> = stream.iter().collect();
+    ///
+    /// assert_eq!(Some(1), head);
+    /// assert_eq!([1, 2, 3].as_slice(), vals.as_slice());
+    /// ```
+    pub fn peek(self) -> Future<(Option<T>, Stream<T, E>), E> {
+        self.and_then(move |head| {
+            let peeked = head.as_ref().map(|&(ref v, _)| v.clone());
+            Future::of((peeked, Future::of(head).as_stream()))
+        })
+    }
+}
+
+impl<T: Send + Clone, E: Send + Clone> Stream<T, E> {
+    /// Returns two streams that both yield every value of `self`. The
+    /// upstream only advances once both streams have requested the next
+    /// value, so the slower consumer gates the faster one.
+    ///
+    /// Errors are cloned to both streams. If either stream is dropped, the
+    /// upstream is no longer driven and the other stream is canceled.
+    pub fn split(self) -> (Stream<T, E>, Stream<T, E>) {
+        let (s1, g1) = Stream::pair();
+        let (s2, g2) = Stream::pair();
+
+        split(self, g1, g2);
+
+        (s1, s2)
+    }
+}
+
+// Waits for both consumers to request the next value, then pulls it from the
+// upstream and sends a copy to each of them.
+fn split<T, E>(upstream: Stream<T, E>, g1: Generate<T, E>, g2: Generate<T, E>)
+        where T: Send + Clone, E: Send + Clone {
+
+    async::join((g1, g2)).receive(move |res| {
+        if let Ok((g1, g2)) = res {
+            upstream.receive(move |res| {
+                match res {
+                    Ok(Some((v, rest))) => {
+                        g1.send(v.clone());
+                        g2.send(v);
+                        split(rest, g1, g2);
+                    }
+                    Ok(None) => {
+                        g1.done();
+                        g2.done();
+                    }
+                    Err(e) => {
+                        g1.fail_with(e.clone());
+                        g2.fail_with(e);
+                    }
+                }
+            });
+        }
+    });
+}
+
+// Holds `v` until either `quiet` elapses, in which case it is yielded, or the
+// upstream yields a newer value, which replaces it.
+fn debounce<T: Send, E: Send>(v: T, rest: Stream<T, E>, quiet: Duration) -> Stream<T, E> {
+    async::select((rest, Future::delay(quiet)))
+        .and_then(move |(i, (rest, _))| {
+            if i == 1 {
+                // The quiet period elapsed
+                return Future::of(Some((v, rest.debounce(quiet)))).as_stream();
+            }
+
+            match rest.expect() {
+                Ok(Some((v, rest))) => debounce(v, rest, quiet),
+                Ok(None) => Future::of(Some((v, Future::of(None).as_stream()))).as_stream(),
+                Err(e) => Future::from_result(Err(e)).as_stream(),
+            }
+        }).as_stream()
+}
+
+// Accumulates values into the group for `k` until the upstream yields a value
+// with a different key, which starts the next group.
+fn group<T, E, F, K>(k: K, mut vals: Vec<T>, rest: Stream<T, E>, key: F) -> Stream<(K, Vec<T>), E>
+        where T: Send,
+              E: Send,
+              F: Fn(&T) -> K + Send,
+              K: PartialEq + Send {
+    rest.handle(move |res| {
+        match res {
+            Ok(Some((v, rest))) => {
+                let next = key(&v);
+
+                if next == k {
+                    vals.push(v);
+                    group(k, vals, rest, key)
+                } else {
+                    Future::of(Some(((k, vals), group(next, vec![v], rest, key)))).as_stream()
+                }
+            }
+            Ok(None) => Future::of(Some(((k, vals), Future::of(None).as_stream()))).as_stream(),
+            Err(e) => Future::from_result(Err(e)).as_stream(),
+        }
+    }).as_stream()
+}
+
+impl<A: Async<Error=E>, E: Send> Stream<A, E> {
+    /// Returns a stream yielding the values of the async values yielded by
+    /// `self`, in order. Up to `n` async values are pulled from the upstream
+    /// ahead of the one currently being waited on, so at most `n` of them
+    /// are in flight at any time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is zero.
+    pub fn buffered(self, n: usize) -> Stream<A::Value, E> {
+        assert!(n > 0, "buffer size must be greater than zero");
+        buffer(Some(self), RingBuf::new(), n)
+    }
+}
+
+// Fills the buffer from the upstream until it contains `n` async values or
+// the upstream is done, then yields the value of the oldest one. Interest is
+// registered in each async value as soon as it is buffered, so that lazy ones
+// start right away instead of when they reach the front of the buffer.
+fn buffer<A, E>(upstream: Option<Stream<A, E>>, mut pending: RingBuf<Future<A::Value, E>>, n: usize) -> Stream<A::Value, E>
+        where A: Async<Error=E>, E: Send {
+
+    match upstream {
+        Some(upstream) if pending.len() < n => {
+            upstream.handle(move |res| {
+                match res {
+                    Ok(Some((a, rest))) => {
+                        let (f, complete) = Future::pair();
+                        a.receive(move |res| complete.resolve(res));
+
+                        pending.push_back(f);
+                        buffer(Some(rest), pending, n)
+                    }
+                    Ok(None) => buffer(None, pending, n),
+                    Err(e) => Future::from_result(Err(e)).as_stream(),
+                }
+            }).as_stream()
+        }
+        upstream => {
+            match pending.pop_front() {
+                Some(a) => {
+                    a.handle(move |res| {
+                        match res {
+                            Ok(v) => Future::of(Some((v, buffer(upstream, pending, n)))).as_stream(),
+                            Err(e) => Future::from_result(Err(e)).as_stream(),
+                        }
+                    }).as_stream()
+                }
+                None => Future::of(None).as_stream(),
+            }
+        }
+    }
 }
 
 impl<T: Send, E: Send> Async for Stream<T, E> {
@@ -206,14 +597,45 @@ impl<T: Send, E: Send> Cancel<Stream<T, E>> for CancelStream<T, E> {
     }
 }
 
+/// The producer half of a stream.
+///
+/// A stream holds at most one value that has not been consumed yet. The
+/// producer applies backpressure by waiting until the consumer requests the
+/// next value before sending it. `Generate` is itself an async value that is
+/// realized once the consumer requests the next value, so demand can be
+/// awaited with `await`, `receive`, or any of the `Async` combinators. It
+/// fails if the consumer drops the stream.
 pub struct Generate<T: Send, E: Send> {
     core: OptionCore<Stream<T, E>>,
 }
 
 impl<T: Send, E: Send> Generate<T, E> {
+    /// Sends the next value of the stream.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the previously sent value has not been consumed yet. Use
+    /// `try_send` when that may be the case.
     pub fn send(&self, val: T) {
+        if let Err(_) = self.try_send(val) {
+            panic!("the previous value has not been consumed yet");
+        }
+    }
+
+    /// Sends the next value of the stream. If the previously sent value has
+    /// not been consumed yet, the value is returned back.
+    pub fn try_send(&self, val: T) -> Result<(), T> {
+        // There is only a single producer, so a pending value cannot be
+        // provided concurrently. At worst, the consumer takes it right after
+        // the check and the value is rejected conservatively.
+        if self.core.get().producer_is_pending() {
+            return Err(val);
+        }
+
         let rest = Stream { core: self.core.clone() };
         self.core.get().complete(Ok(Some((val, rest))), false);
+
+        Ok(())
     }
 
     pub fn done(self) {
@@ -228,6 +650,12 @@ impl<T: Send, E: Send> Generate<T, E> {
         self.core.take().complete(Err(AsyncError::wrap(err)), true);
     }
 
+    // Ends the stream with any `AsyncError`, including a cancellation or a
+    // timeout.
+    fn fail_with(mut self, err: AsyncError<E>) {
+        self.core.take().complete(Err(err), true);
+    }
+
     pub fn is_ready(&self) -> bool {
         self.core.get().producer_is_ready()
     }
@@ -251,6 +679,8 @@ impl<T: Send, E: Send> Generate<T, E> {
         self.core.take().producer_ready(f);
     }
 
+    /// Blocks the thread until the consumer requests the next value. Returns
+    /// an error if the consumer canceled the stream.
     pub fn await(self) -> AsyncResult<Generate<T, E>, ()> {
         self.core.get().producer_await();
         self.poll().ok().expect("Generate not ready")
@@ -336,7 +766,15 @@ impl<T: Send, E: Send> Iterator for StreamIter<T, E> {
                 let _ = self.core.take();
                 None
             }
-            Err(_) => unimplemented!(),
+            Err(e) => {
+                let _ = self.core.take();
+
+                if e.is_cancellation() {
+                    panic!("the stream was canceled");
+                }
+
+                panic!("the stream failed");
+            }
         }
     }
 }
@@ -0,0 +1,120 @@
+use super::{timer, Async, Future, Complete, Cancel, AsyncError};
+use super::timer::Delay;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+
+/// Completes `complete` with the result of `async` if it is realized before
+/// the duration elapses. Otherwise, `async` is canceled and `complete` is
+/// completed with a `TimeoutError`.
+///
+/// The async value is raced against an entry of the shared timer, which is
+/// removed as soon as the value is realized.
+pub fn timeout<A: Async>(async: A, dur: Duration, complete: Complete<A::Value, A::Error>) {
+    let (elapsed, c) = Future::<(), ()>::pair();
+    let timeout = Timeout::new(complete, timer::delay(dur, c));
+
+    let t = timeout.clone();
+    elapsed.receive(move |res| {
+        // The delayed future is canceled if the value arrives in time
+        if res.is_ok() {
+            t.elapsed();
+        }
+    });
+
+    // Register interest in the async value. The callback may be invoked
+    // immediately if the value is already realized.
+    let t = timeout.clone();
+    let cancel = async.ready(move |a| t.ready(a));
+
+    timeout.track(cancel);
+}
+
+struct Timeout<A: Async> {
+    inner: Arc<Mutex<State<A>>>,
+}
+
+impl<A: Async> Timeout<A> {
+    fn new(complete: Complete<A::Value, A::Error>, delay: Delay) -> Timeout<A> {
+        let state = State {
+            complete: Some(complete),
+            cancel: None,
+            delay: Some(delay),
+        };
+
+        Timeout { inner: Arc::new(Mutex::new(state)) }
+    }
+
+    fn lock(&self) -> MutexGuard<State<A>> {
+        self.inner.lock()
+            .ok().expect("something went wrong")
+    }
+
+    // Stores the cancel token if neither side has won the race yet.
+    // Otherwise, withdraws interest in the async value right away, which is a
+    // no-op if it is the side that won.
+    fn track(&self, cancel: A::Cancel) {
+        let mut state = self.lock();
+
+        if state.complete.is_some() {
+            state.cancel = Some(cancel);
+            return;
+        }
+
+        drop(state);
+        drop(cancel.cancel());
+    }
+
+    fn ready(&self, async: A) {
+        let (complete, delay) = {
+            let mut state = self.lock();
+
+            match state.complete.take() {
+                Some(complete) => (complete, state.delay.take()),
+                // The timeout already fired, drop the value
+                None => return,
+            }
+        };
+
+        // Release the timer entry
+        if let Some(delay) = delay {
+            delay.cancel();
+        }
+
+        complete.resolve(async.expect());
+    }
+
+    fn elapsed(&self) {
+        let (complete, cancel) = {
+            let mut state = self.lock();
+
+            match state.complete.take() {
+                Some(complete) => (complete, state.cancel.take()),
+                // The value was realized in time
+                None => return,
+            }
+        };
+
+        debug!("timeout; deadline reached, canceling async value");
+
+        // Withdraw interest in the async value. If the callback has already
+        // fired, it will see that the timeout fired. Dropping the value
+        // cancels it.
+        if let Some(c) = cancel {
+            drop(c.cancel());
+        }
+
+        complete.resolve(Err(AsyncError::timed_out()));
+    }
+}
+
+impl<A: Async> Clone for Timeout<A> {
+    fn clone(&self) -> Timeout<A> {
+        Timeout { inner: self.inner.clone() }
+    }
+}
+
+struct State<A: Async> {
+    complete: Option<Complete<A::Value, A::Error>>,
+    cancel: Option<A::Cancel>,
+    delay: Option<Delay>,
+}
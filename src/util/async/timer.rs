@@ -0,0 +1,263 @@
+use super::Complete;
+use util::ThreadPool;
+use std::mem;
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex, MutexGuard, Condvar, Once, ONCE_INIT};
+use std::thread::Thread;
+use std::time::Duration;
+use time::SteadyTime;
+
+/// Completes `complete` once `dur` has elapsed. All delays are tracked by a
+/// single timer thread which is started the first time a delay is scheduled.
+/// The timer thread hands completions off to a pool of two threads, so a slow
+/// continuation only holds up other due entries once both dispatch threads
+/// are busy.
+///
+/// The entry is released as soon as the returned handle is canceled. If the
+/// consumer cancels the future instead, the entry lingers until the deadline.
+pub fn delay<E: Send>(dur: Duration, complete: Complete<(), E>) -> Delay {
+    let deadline = SteadyTime::now() + dur;
+    Delay { key: timer().schedule(deadline, Box::new(complete)) }
+}
+
+/// A handle to a pending delay.
+pub struct Delay {
+    key: Key,
+}
+
+impl Delay {
+    /// Removes the entry from the timer. The complete is dropped, canceling
+    /// the delayed future. Returns false if the entry already fired.
+    pub fn cancel(self) -> bool {
+        timer().cancel(self.key)
+    }
+}
+
+static INIT: Once = ONCE_INIT;
+static mut TIMER: *const Timer = 0 as *const Timer;
+
+// Returns the shared timer, starting the timer thread if needed. The timer
+// is never freed.
+fn timer() -> &'static Timer {
+    unsafe {
+        INIT.call_once(|| {
+            TIMER = mem::transmute(Box::new(Timer::start(DISPATCH_POOL_SIZE)));
+        });
+
+        &*TIMER
+    }
+}
+
+const DISPATCH_POOL_SIZE: u32 = 2;
+
+// Stops the timer thread when dropped. Pending entries are released,
+// canceling their futures.
+struct Timer {
+    inner: Arc<Inner>,
+}
+
+impl Timer {
+    fn start(pool_size: u32) -> Timer {
+        let inner = Arc::new(Inner {
+            state: Mutex::new(State {
+                entries: BTreeMap::new(),
+                next_id: 0,
+                shutdown: false,
+            }),
+            condvar: Condvar::new(),
+        });
+
+        let i = inner.clone();
+        Thread::spawn(move || i.run(ThreadPool::fixed_size(pool_size)));
+
+        Timer { inner: inner }
+    }
+
+    fn schedule(&self, deadline: SteadyTime, entry: Box<Entry>) -> Key {
+        let mut state = self.inner.lock();
+
+        let key = (deadline, state.next_id);
+
+        state.next_id += 1;
+        state.entries.insert(key, entry);
+
+        // The new entry may be due before the one the timer thread is
+        // currently waiting on.
+        self.inner.condvar.notify_one();
+
+        key
+    }
+
+    fn cancel(&self, key: Key) -> bool {
+        let entry = self.inner.lock().entries.remove(&key);
+
+        // The entry is dropped without holding the lock, since dropping the
+        // complete may invoke the consumer's callback.
+        entry.is_some()
+    }
+
+    // Returns the number of pending entries
+    #[cfg(test)]
+    fn len(&self) -> usize {
+        self.inner.lock().entries.len()
+    }
+}
+
+impl Drop for Timer {
+    fn drop(&mut self) {
+        self.inner.lock().shutdown = true;
+        self.inner.condvar.notify_one();
+    }
+}
+
+struct Inner {
+    state: Mutex<State>,
+    condvar: Condvar,
+}
+
+impl Inner {
+    fn lock(&self) -> MutexGuard<State> {
+        self.state.lock()
+            .ok().expect("something went wrong")
+    }
+
+    fn run(&self, dispatch: ThreadPool) {
+        let mut state = self.lock();
+
+        while !state.shutdown {
+            let now = SteadyTime::now();
+            let due = state.take_due(now);
+
+            if !due.is_empty() {
+                drop(state);
+
+                debug!("timer; firing {} entries", due.len());
+
+                for entry in due.into_iter() {
+                    dispatch.run(move || entry.fire());
+                }
+
+                state = self.lock();
+                continue;
+            }
+
+            let next = state.entries.keys().next().map(|key| key.0 - now);
+
+            state = match next {
+                Some(dur) => {
+                    self.condvar.wait_timeout(state, dur)
+                        .ok().expect("something went wrong").0
+                }
+                None => {
+                    self.condvar.wait(state)
+                        .ok().expect("something went wrong")
+                }
+            };
+        }
+
+        // Release the pending entries without holding the lock, then let the
+        // continuations that were already dispatched finish.
+        let keys: Vec<Key> = state.entries.keys().map(|key| *key).collect();
+        let pending = state.remove_all(keys);
+
+        drop(state);
+
+        debug!("timer; shutting down, releasing {} entries", pending.len());
+        drop(pending);
+
+        dispatch.shutdown();
+        dispatch.await_termination();
+    }
+}
+
+// Entries are keyed by deadline, then by scheduling order, so that iterating
+// the map yields the earliest deadline first and every key is unique.
+type Key = (SteadyTime, u64);
+
+struct State {
+    entries: BTreeMap<Key, Box<Entry>>,
+    next_id: u64,
+    shutdown: bool,
+}
+
+impl State {
+    // Removes and returns the entries that are due
+    fn take_due(&mut self, now: SteadyTime) -> Vec<Box<Entry>> {
+        let keys: Vec<Key> = self.entries.keys()
+            .take_while(|key| key.0 <= now)
+            .map(|key| *key)
+            .collect();
+
+        self.remove_all(keys)
+    }
+
+    fn remove_all(&mut self, keys: Vec<Key>) -> Vec<Box<Entry>> {
+        keys.iter()
+            .map(|key| self.entries.remove(key).expect("entry already removed"))
+            .collect()
+    }
+}
+
+// A pending timer entry
+trait Entry : Send {
+    fn fire(self: Box<Self>);
+}
+
+impl<E: Send> Entry for Complete<(), E> {
+    fn fire(self: Box<Complete<(), E>>) {
+        (*self).complete(());
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Timer;
+    use util::async::*;
+    use std::time::Duration;
+    use time::SteadyTime;
+
+    #[test]
+    pub fn test_canceling_releases_entry() {
+        let timer = Timer::start(1);
+        let (f, c) = Future::<(), ()>::pair();
+
+        let key = timer.schedule(SteadyTime::now() + millis(10_000), Box::new(c));
+        assert_eq!(1, timer.len());
+
+        assert!(timer.cancel(key));
+        assert_eq!(0, timer.len());
+
+        // Releasing the entry canceled the delayed future
+        assert!(f.await().unwrap_err().is_cancellation());
+
+        // The entry can only be removed once
+        assert!(!timer.cancel(key));
+    }
+
+    #[test]
+    pub fn test_dropping_timer_releases_entries() {
+        let timer = Timer::start(1);
+        let (f, c) = Future::<(), ()>::pair();
+
+        timer.schedule(SteadyTime::now() + millis(10_000), Box::new(c));
+        drop(timer);
+
+        // The timer thread released the entry on its way out
+        assert!(f.await().unwrap_err().is_cancellation());
+    }
+
+    #[test]
+    pub fn test_fired_entry_is_released() {
+        let timer = Timer::start(1);
+        let (f, c) = Future::<(), ()>::pair();
+
+        timer.schedule(SteadyTime::now() + millis(10), Box::new(c));
+
+        assert!(f.await().is_ok());
+        assert_eq!(0, timer.len());
+    }
+
+    fn millis(num: uint) -> Duration {
+        Duration::milliseconds(num as i64)
+    }
+}
@@ -0,0 +1,206 @@
+use super::{Async, Complete, Cancel, BoxedReceive};
+use std::collections::HashMap;
+use std::mem;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// Cancels every async value bound to one of its tokens.
+///
+/// ```
+/// use syncbox::util::async::*;
+///
+/// let source = CancelSource::new();
+/// let (f, c) = Future::<u32, ()>::pair();
+/// let f = f.with_token(source.token());
+///
+/// source.cancel();
+/// c.complete(123);
+///
+/// assert!(f.await().unwrap_err().is_cancellation());
+/// ```
+pub struct CancelSource {
+    inner: Arc<Inner>,
+}
+
+impl CancelSource {
+    pub fn new() -> CancelSource {
+        let inner = Inner {
+            state: Mutex::new(State {
+                canceled: false,
+                callbacks: HashMap::new(),
+                next_id: 0,
+            }),
+        };
+
+        CancelSource { inner: Arc::new(inner) }
+    }
+
+    /// Returns a token that async values can be bound to with
+    /// `Async::with_token`.
+    pub fn token(&self) -> CancelToken {
+        CancelToken { inner: self.inner.clone() }
+    }
+
+    /// Cancels every async value bound to a token of this source, including
+    /// the ones bound from now on. Does nothing if already canceled.
+    pub fn cancel(&self) {
+        let callbacks = {
+            let mut state = self.inner.lock();
+
+            state.canceled = true;
+            mem::replace(&mut state.callbacks, HashMap::new())
+        };
+
+        // Invoke the callbacks without holding the lock, since they complete
+        // downstream futures.
+        for (_, f) in callbacks.into_iter() {
+            f.receive_boxed(());
+        }
+    }
+
+    pub fn is_canceled(&self) -> bool {
+        self.inner.lock().canceled
+    }
+}
+
+/// A handle to a `CancelSource`, used to bind async values to it.
+pub struct CancelToken {
+    inner: Arc<Inner>,
+}
+
+impl CancelToken {
+    pub fn is_canceled(&self) -> bool {
+        self.inner.lock().canceled
+    }
+
+    // Invokes `f` once the source is canceled, immediately if it already is.
+    // Returns the id to deregister the callback with, unless it has already
+    // been invoked.
+    fn on_cancel(&self, f: Box<BoxedReceive<()>>) -> Option<u64> {
+        let mut state = self.inner.lock();
+
+        if state.canceled {
+            drop(state);
+            f.receive_boxed(());
+            return None;
+        }
+
+        let id = state.next_id;
+
+        state.next_id += 1;
+        state.callbacks.insert(id, f);
+
+        Some(id)
+    }
+
+    // Releases a callback that is no longer needed. Does nothing if the
+    // source has been canceled meanwhile.
+    fn deregister(&self, id: u64) {
+        let f = self.inner.lock().callbacks.remove(&id);
+
+        // Dropped without holding the lock
+        drop(f);
+    }
+}
+
+impl Clone for CancelToken {
+    fn clone(&self) -> CancelToken {
+        CancelToken { inner: self.inner.clone() }
+    }
+}
+
+/// Completes `complete` with the result of `async` unless the token is
+/// canceled first, in which case interest in `async` is withdrawn, canceling
+/// it, and `complete` is dropped, canceling the downstream future.
+pub fn with_token<A: Async>(async: A, token: CancelToken, complete: Complete<A::Value, A::Error>) {
+    // Whichever of the result or the cancellation comes first takes the
+    // complete. Once the result is in, the callback is deregistered from the
+    // token so that long lived sources don't accumulate them.
+    let binding = Arc::new(Mutex::new(Binding {
+        complete: Some(complete),
+        cancel: None,
+        id: None,
+    }));
+
+    let b = binding.clone();
+    let id = token.on_cancel(Box::new(move |()| {
+        let (complete, cancel) = {
+            let mut binding = lock(&b);
+            (binding.complete.take(), binding.cancel.take())
+        };
+
+        if let Some(c) = cancel {
+            drop(c.cancel());
+        }
+
+        drop(complete);
+    }));
+
+    // The token was already canceled, dropping `async` cancels it
+    if lock(&binding).complete.is_none() {
+        return;
+    }
+
+    let b = binding.clone();
+    let t = token.clone();
+    let cancel = async.ready(move |a| {
+        let (complete, id) = {
+            let mut binding = lock(&b);
+            (binding.complete.take(), binding.id.take())
+        };
+
+        if let Some(complete) = complete {
+            if let Some(id) = id {
+                t.deregister(id);
+            }
+
+            complete.resolve(a.expect());
+        }
+    });
+
+    let mut state = lock(&binding);
+
+    if state.complete.is_some() {
+        state.cancel = Some(cancel);
+        state.id = id;
+        return;
+    }
+
+    drop(state);
+
+    // Either the result is already in and the callback is released here, or
+    // the token was canceled before the cancel token could be stored and the
+    // interest is withdrawn here.
+    if let Some(id) = id {
+        token.deregister(id);
+    }
+
+    drop(cancel.cancel());
+}
+
+struct Binding<A: Async> {
+    complete: Option<Complete<A::Value, A::Error>>,
+    cancel: Option<A::Cancel>,
+    id: Option<u64>,
+}
+
+fn lock<A: Async>(binding: &Mutex<Binding<A>>) -> MutexGuard<Binding<A>> {
+    binding.lock()
+        .ok().expect("something went wrong")
+}
+
+struct Inner {
+    state: Mutex<State>,
+}
+
+impl Inner {
+    fn lock(&self) -> MutexGuard<State> {
+        self.state.lock()
+            .ok().expect("something went wrong")
+    }
+}
+
+struct State {
+    canceled: bool,
+    callbacks: HashMap<u64, Box<BoxedReceive<()>>>,
+    next_id: u64,
+}
@@ -0,0 +1,388 @@
+use std::collections::{HashMap, RingBuf};
+use std::sync::{Arc, Mutex, MutexGuard, Condvar};
+
+/// A bounded queue in which every subscriber receives a clone of every
+/// element.
+///
+/// Elements are stored once in a shared ring buffer and each subscriber keeps
+/// a cursor into it. An element is released once every subscriber has taken
+/// it, so producers block when the slowest subscriber lags `capacity`
+/// elements behind. Subscribers only receive the elements inserted after they
+/// subscribed, and elements inserted while there are no subscribers are
+/// dropped.
+///
+/// The current implementation is based on a single mutex and two condition
+/// variables.
+pub struct BroadcastQueue<T> {
+    inner: Arc<QueueInner<T>>,
+}
+
+impl<T: Send + Clone> BroadcastQueue<T> {
+    pub fn with_capacity(capacity: usize) -> BroadcastQueue<T> {
+        assert!(capacity > 0, "capacity must be greater than zero");
+
+        BroadcastQueue {
+            inner: Arc::new(QueueInner::new(capacity))
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.inner.capacity
+    }
+
+    /// Returns the number of elements that have not yet been taken by every
+    /// subscriber.
+    pub fn len(&self) -> usize {
+        self.inner.lock().buf.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns a new subscriber that receives every element inserted from
+    /// now on.
+    pub fn subscribe(&self) -> Subscriber<T> {
+        let id = self.inner.subscribe();
+        Subscriber { inner: self.inner.clone(), id: id }
+    }
+
+    /// Inserts the element into the queue if the slowest subscriber has room
+    /// for it. The element is returned if the queue is at capacity.
+    pub fn offer(&self, e: T) -> Result<(), T> {
+        self.inner.offer(e)
+    }
+
+    /// Inserts into the queue, blocking until the slowest subscriber has room
+    /// for the element.
+    pub fn put(&self, e: T) {
+        self.inner.put(e);
+    }
+}
+
+impl<T: Send + Clone> Clone for BroadcastQueue<T> {
+    fn clone(&self) -> BroadcastQueue<T> {
+        BroadcastQueue { inner: self.inner.clone() }
+    }
+}
+
+/// A consumer of a `BroadcastQueue`. Dropping the subscriber leaves the
+/// queue, releasing any elements that only it had yet to take.
+pub struct Subscriber<T> {
+    inner: Arc<QueueInner<T>>,
+    id: usize,
+}
+
+impl<T: Send + Clone> Subscriber<T> {
+    /// Returns the number of elements available to this subscriber.
+    pub fn len(&self) -> usize {
+        let state = self.inner.lock();
+        state.tail() - state.cursor(self.id)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn poll(&self) -> Option<T> {
+        let mut state = self.inner.lock();
+        self.inner.next(&mut *state, self.id)
+    }
+
+    /// Takes from the queue, blocking until there is an element available to
+    /// this subscriber.
+    pub fn take(&self) -> T {
+        let mut state = self.inner.lock();
+
+        loop {
+            if let Some(val) = self.inner.next(&mut *state, self.id) {
+                return val;
+            }
+
+            state = self.inner.not_empty.wait(state)
+                .ok().expect("something went wrong");
+        }
+    }
+}
+
+#[unsafe_destructor]
+impl<T: Send + Clone> Drop for Subscriber<T> {
+    fn drop(&mut self) {
+        let mut state = self.inner.lock();
+
+        state.cursors.remove(&self.id);
+
+        if state.release() {
+            self.inner.not_full.notify_all();
+        }
+    }
+}
+
+struct QueueInner<T> {
+
+    // Maximum number of elements that can be held for the slowest subscriber
+    capacity: usize,
+
+    // The buffer and the cursors, guarded by a single lock
+    state: Mutex<State<T>>,
+
+    // Wait queue for waiting takes
+    not_empty: Condvar,
+
+    // Wait queue for waiting puts
+    not_full: Condvar,
+}
+
+impl<T: Send + Clone> QueueInner<T> {
+    fn new(capacity: usize) -> QueueInner<T> {
+        let state = State {
+            buf: RingBuf::with_capacity(capacity),
+            head: 0,
+            cursors: HashMap::new(),
+            next_id: 0,
+        };
+
+        QueueInner {
+            capacity: capacity,
+            state: Mutex::new(state),
+            not_empty: Condvar::new(),
+            not_full: Condvar::new(),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<State<T>> {
+        self.state.lock()
+            .ok().expect("something went wrong")
+    }
+
+    fn subscribe(&self) -> usize {
+        let mut state = self.lock();
+
+        let id = state.next_id;
+        let tail = state.tail();
+
+        state.next_id += 1;
+        state.cursors.insert(id, tail);
+
+        id
+    }
+
+    fn offer(&self, e: T) -> Result<(), T> {
+        let mut state = self.lock();
+
+        if state.buf.len() == self.capacity {
+            return Err(e);
+        }
+
+        self.push(&mut *state, e);
+
+        Ok(())
+    }
+
+    fn put(&self, e: T) {
+        let mut state = self.lock();
+
+        while state.buf.len() == self.capacity {
+            state = self.not_full.wait(state)
+                .ok().expect("something went wrong");
+        }
+
+        self.push(&mut *state, e);
+    }
+
+    fn push(&self, state: &mut State<T>, e: T) {
+        // Nobody would ever take the element
+        if state.cursors.is_empty() {
+            return;
+        }
+
+        state.buf.push_back(e);
+
+        // Every subscriber may be waiting on the same element
+        self.not_empty.notify_all();
+    }
+
+    // Returns a clone of the next element for the subscriber, advancing its
+    // cursor.
+    fn next(&self, state: &mut State<T>, id: usize) -> Option<T> {
+        let cursor = state.cursor(id);
+
+        if cursor == state.tail() {
+            return None;
+        }
+
+        let val = state.buf[cursor - state.head].clone();
+        state.cursors.insert(id, cursor + 1);
+
+        if state.release() {
+            self.not_full.notify_all();
+        }
+
+        Some(val)
+    }
+}
+
+struct State<T> {
+    // Elements not yet taken by every subscriber
+    buf: RingBuf<T>,
+
+    // Sequence number of the element at the front of the buffer
+    head: usize,
+
+    // Sequence number of the next element to take, per subscriber
+    cursors: HashMap<usize, usize>,
+
+    // Identifier assigned to the next subscriber
+    next_id: usize,
+}
+
+impl<T: Send + Clone> State<T> {
+    fn cursor(&self, id: usize) -> usize {
+        *self.cursors.get(&id).expect("unknown subscriber")
+    }
+
+    // Sequence number of the next element to be inserted
+    fn tail(&self) -> usize {
+        self.head + self.buf.len()
+    }
+
+    // Drops the elements that every subscriber has taken. Returns true if any
+    // element was dropped.
+    fn release(&mut self) -> bool {
+        let tail = self.tail();
+        let min = self.cursors.values().map(|&c| c).min().unwrap_or(tail);
+
+        for _ in range(self.head, min) {
+            self.buf.pop_front();
+        }
+
+        let released = min > self.head;
+        self.head = min;
+
+        released
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::BroadcastQueue;
+    use std::old_io::timer::sleep;
+    use std::time::Duration;
+    use std::thread::Thread;
+    use std::sync::mpsc::sync_channel;
+
+    #[test]
+    pub fn test_every_subscriber_receives_every_element() {
+        let q = BroadcastQueue::with_capacity(4);
+        let s1 = q.subscribe();
+        let s2 = q.subscribe();
+
+        for i in range(1, 4u) {
+            q.put(i);
+        }
+
+        let v1: Vec<uint> = range(0, 3u).map(|_| s1.take()).collect();
+        let v2: Vec<uint> = range(0, 3u).map(|_| s2.take()).collect();
+
+        assert_eq!(vec![1, 2, 3], v1);
+        assert_eq!(vec![1, 2, 3], v2);
+
+        assert!(s1.poll().is_none());
+        assert!(q.is_empty());
+    }
+
+    #[test]
+    pub fn test_late_subscriber_only_sees_later_elements() {
+        let q = BroadcastQueue::with_capacity(4);
+        let s1 = q.subscribe();
+
+        q.put(1u);
+        q.put(2u);
+
+        let s2 = q.subscribe();
+
+        q.put(3u);
+
+        assert_eq!(3, s1.len());
+        assert_eq!(1, s2.len());
+
+        assert_eq!(Some(3), s2.poll());
+        assert!(s2.poll().is_none());
+
+        assert_eq!(Some(1), s1.poll());
+    }
+
+    #[test]
+    pub fn test_elements_without_subscribers_are_dropped() {
+        let q = BroadcastQueue::with_capacity(1);
+
+        q.put(1u);
+        q.put(2u);
+
+        assert!(q.is_empty());
+
+        let s = q.subscribe();
+        assert!(s.poll().is_none());
+    }
+
+    #[test]
+    pub fn test_put_blocks_on_slowest_subscriber() {
+        let q = BroadcastQueue::with_capacity(1);
+        let p = q.clone();
+        let fast = q.subscribe();
+        let slow = q.subscribe();
+        let (tx, rx) = sync_channel(0);
+
+        q.put(1u);
+        assert_eq!(1, fast.take());
+
+        Thread::spawn(move || {
+            p.put(2u);
+            tx.send(()).unwrap();
+        });
+
+        sleep(millis(20));
+        assert!(rx.try_recv().is_err());
+        assert_eq!(Err(3), q.offer(3));
+
+        assert_eq!(1, slow.take());
+        rx.recv().unwrap();
+
+        assert_eq!(2, fast.take());
+        assert_eq!(2, slow.take());
+    }
+
+    #[test]
+    pub fn test_leaving_releases_elements() {
+        let q = BroadcastQueue::with_capacity(1);
+        let s1 = q.subscribe();
+        let s2 = q.subscribe();
+
+        q.put(1u);
+        assert_eq!(1, s1.take());
+        assert!(q.offer(2).is_err());
+
+        drop(s2);
+
+        assert!(q.offer(2).is_ok());
+        assert_eq!(2, s1.take());
+    }
+
+    #[test]
+    pub fn test_take_blocks_when_empty() {
+        let q = BroadcastQueue::with_capacity(1);
+        let p = q.clone();
+        let s = q.subscribe();
+
+        Thread::spawn(move || {
+            sleep(millis(20));
+            p.put(1u);
+        });
+
+        assert_eq!(1, s.take());
+    }
+
+    fn millis(num: uint) -> Duration {
+        Duration::milliseconds(num as i64)
+    }
+}
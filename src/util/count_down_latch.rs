@@ -0,0 +1,166 @@
+use super::{deadline, wait_until};
+use std::sync::{Arc, Mutex, Condvar};
+use std::time::Duration;
+
+/// A synchronization aid that allows threads to wait until a number of
+/// operations being performed in other threads complete.
+///
+/// The latch is initialized with a count. Each call to `count_down`
+/// decrements it, and once it reaches zero all waiting threads are released.
+/// The latch cannot be reset, so further calls to `wait` return immediately
+/// and further calls to `count_down` have no effect.
+pub struct CountDownLatch {
+    inner: Arc<Inner>,
+}
+
+impl CountDownLatch {
+    pub fn new(count: usize) -> CountDownLatch {
+        let inner = Inner {
+            count: Mutex::new(count),
+            condvar: Condvar::new(),
+        };
+
+        CountDownLatch { inner: Arc::new(inner) }
+    }
+
+    /// Returns the current count.
+    pub fn count(&self) -> usize {
+        *self.inner.count.lock()
+            .ok().expect("something went wrong")
+    }
+
+    /// Decrements the count, releasing all waiting threads if it reaches
+    /// zero. Does nothing if the count is already zero.
+    pub fn count_down(&self) {
+        let mut count = self.inner.count.lock()
+            .ok().expect("something went wrong");
+
+        if *count == 0 {
+            return;
+        }
+
+        *count -= 1;
+
+        if *count == 0 {
+            self.inner.condvar.notify_all();
+        }
+    }
+
+    /// Blocks until the count reaches zero.
+    pub fn wait(&self) {
+        self.inner.wait_for(Duration::max_value());
+    }
+
+    /// Blocks until the count reaches zero, waiting up to `timeout`. Returns
+    /// false if the count is still greater than zero once the timeout
+    /// elapses.
+    pub fn wait_timeout(&self, timeout: Duration) -> bool {
+        self.inner.wait_for(timeout)
+    }
+}
+
+impl Clone for CountDownLatch {
+    fn clone(&self) -> CountDownLatch {
+        CountDownLatch { inner: self.inner.clone() }
+    }
+}
+
+struct Inner {
+    count: Mutex<usize>,
+    condvar: Condvar,
+}
+
+impl Inner {
+    fn wait_for(&self, dur: Duration) -> bool {
+        let deadline = deadline(dur);
+
+        let mut count = self.count.lock()
+            .ok().expect("something went wrong");
+
+        while *count > 0 {
+            count = match wait_until(&self.condvar, count, deadline) {
+                Some(count) => count,
+                None => return false,
+            };
+        }
+
+        true
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::CountDownLatch;
+    use std::old_io::timer::sleep;
+    use std::time::Duration;
+    use std::thread::Thread;
+    use std::sync::mpsc::channel;
+
+    #[test]
+    pub fn test_count_down_releases_waiter() {
+        let latch = CountDownLatch::new(3);
+        let (tx, rx) = channel();
+
+        {
+            let latch = latch.clone();
+
+            Thread::spawn(move || {
+                latch.wait();
+                tx.send(()).unwrap();
+            });
+        }
+
+        for _ in range(0, 3u) {
+            let latch = latch.clone();
+
+            Thread::spawn(move || {
+                sleep(millis(20));
+                latch.count_down();
+            });
+        }
+
+        rx.recv().unwrap();
+        assert_eq!(0, latch.count());
+    }
+
+    #[test]
+    pub fn test_count_down_past_zero() {
+        let latch = CountDownLatch::new(1);
+
+        latch.count_down();
+        latch.count_down();
+
+        assert_eq!(0, latch.count());
+
+        // Returns immediately
+        latch.wait();
+    }
+
+    #[test]
+    pub fn test_wait_timeout_elapses() {
+        let latch = CountDownLatch::new(1);
+
+        assert!(!latch.wait_timeout(millis(20)));
+        assert_eq!(1, latch.count());
+    }
+
+    #[test]
+    pub fn test_wait_timeout_released() {
+        let latch = CountDownLatch::new(1);
+
+        {
+            let latch = latch.clone();
+
+            Thread::spawn(move || {
+                sleep(millis(20));
+                latch.count_down();
+            });
+        }
+
+        assert!(latch.wait_timeout(millis(1_000)));
+    }
+
+    fn millis(num: uint) -> Duration {
+        Duration::milliseconds(num as i64)
+    }
+}
@@ -0,0 +1,397 @@
+use std::{mem, ptr};
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicIsize, AtomicPtr, Ordering};
+
+/// A work-stealing deque based on the Chase-Lev algorithm.
+///
+/// # Contract
+///
+/// The `Deque` handle is owned by a single worker, which pushes and pops
+/// values at the bottom of the deque. Any number of `Stealer` handles may be
+/// created and sent to other threads in order to steal values from the top.
+/// The owner does not take any locks, and stealers only contend with each
+/// other, or with the owner when a single value is left.
+///
+/// The buffer grows as needed. Buffers that are outgrown may still be read by
+/// concurrent stealers, so they are only freed once the deque is dropped.
+/// Since the buffer doubles in size each time, this at most doubles the
+/// memory used by the deque.
+///
+/// `ThreadPool` gives each of its workers a deque for the tasks submitted
+/// from that worker.
+pub struct Deque<T> {
+    inner: Arc<DequeInner<T>>,
+}
+
+/// A handle that steals values from the top of a `Deque`.
+pub struct Stealer<T> {
+    inner: Arc<DequeInner<T>>,
+}
+
+/// The result of a steal attempt.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Steal<T> {
+    /// The deque was empty
+    Empty,
+    /// Lost a race for the value with another thread, the attempt may be
+    /// retried
+    Abort,
+    /// Successfully stole a value
+    Data(T),
+}
+
+impl<T: Send> Deque<T> {
+    pub fn new() -> Deque<T> {
+        Deque { inner: Arc::new(DequeInner::new()) }
+    }
+
+    /// Returns a handle that steals from this deque.
+    pub fn stealer(&self) -> Stealer<T> {
+        Stealer { inner: self.inner.clone() }
+    }
+
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Pushes the value onto the bottom of the deque, growing the buffer if
+    /// needed.
+    pub fn push(&mut self, val: T) {
+        unsafe { self.inner.push(val) }
+    }
+
+    /// Pops the most recently pushed value from the bottom of the deque.
+    pub fn pop(&mut self) -> Option<T> {
+        unsafe { self.inner.pop() }
+    }
+}
+
+unsafe impl<T: Send> Send for Deque<T> {}
+
+impl<T: Send> Stealer<T> {
+    /// Attempts to steal the least recently pushed value from the top of the
+    /// deque.
+    pub fn steal(&self) -> Steal<T> {
+        unsafe { self.inner.steal() }
+    }
+}
+
+impl<T: Send> Clone for Stealer<T> {
+    fn clone(&self) -> Stealer<T> {
+        Stealer { inner: self.inner.clone() }
+    }
+}
+
+unsafe impl<T: Send> Send for Stealer<T> {}
+
+// Values live in the range [top, bottom) of a circular buffer. The owner
+// pushes and pops by moving `bottom`, stealers take values by incrementing
+// `top` with a CAS. When a single value is left, the owner also takes it with
+// a CAS on `top`, so that exactly one thread wins it.
+//
+// A value is read from the buffer before the CAS that claims it. If the CAS
+// fails, the copy is forgotten since the value belongs to whichever thread
+// won the race.
+struct DequeInner<T> {
+    top: AtomicIsize,
+    bottom: AtomicIsize,
+    buffer: AtomicPtr<Buffer<T>>,
+
+    // Outgrown buffers, kept alive until the deque is dropped
+    retired: Mutex<Vec<*mut Buffer<T>>>,
+}
+
+unsafe impl<T: Send> Send for DequeInner<T> {}
+unsafe impl<T: Send> Sync for DequeInner<T> {}
+
+impl<T: Send> DequeInner<T> {
+    fn new() -> DequeInner<T> {
+        DequeInner {
+            top: AtomicIsize::new(0),
+            bottom: AtomicIsize::new(0),
+            buffer: AtomicPtr::new(Buffer::new(MIN_CAPACITY)),
+            retired: Mutex::new(vec![]),
+        }
+    }
+
+    fn len(&self) -> usize {
+        let b = self.bottom.load(Ordering::SeqCst);
+        let t = self.top.load(Ordering::SeqCst);
+
+        if b > t { (b - t) as usize } else { 0 }
+    }
+
+    // Owner only
+    unsafe fn push(&self, val: T) {
+        let b = self.bottom.load(Ordering::SeqCst);
+        let t = self.top.load(Ordering::SeqCst);
+        let mut buf = self.buffer.load(Ordering::SeqCst);
+
+        if b - t >= (*buf).capacity() as isize - 1 {
+            buf = self.grow(buf, b, t);
+        }
+
+        (*buf).write(b, val);
+        self.bottom.store(b + 1, Ordering::SeqCst);
+    }
+
+    // Owner only
+    unsafe fn pop(&self) -> Option<T> {
+        let b = self.bottom.load(Ordering::SeqCst) - 1;
+        let buf = self.buffer.load(Ordering::SeqCst);
+
+        // Reserve the bottom value before checking for stealers
+        self.bottom.store(b, Ordering::SeqCst);
+
+        let t = self.top.load(Ordering::SeqCst);
+        let size = b - t;
+
+        if size < 0 {
+            // The deque is empty, restore the bottom
+            self.bottom.store(t, Ordering::SeqCst);
+            return None;
+        }
+
+        let val = (*buf).read(b);
+
+        if size > 0 {
+            // More than one value left, no stealer can reach this one
+            return Some(val);
+        }
+
+        // Last value, race the stealers for it
+        let won = self.top.compare_and_swap(t, t + 1, Ordering::SeqCst) == t;
+        self.bottom.store(t + 1, Ordering::SeqCst);
+
+        if won {
+            Some(val)
+        } else {
+            mem::forget(val);
+            None
+        }
+    }
+
+    unsafe fn steal(&self) -> Steal<T> {
+        let t = self.top.load(Ordering::SeqCst);
+        let b = self.bottom.load(Ordering::SeqCst);
+
+        if b - t <= 0 {
+            return Steal::Empty;
+        }
+
+        let buf = self.buffer.load(Ordering::SeqCst);
+        let val = (*buf).read(t);
+
+        if self.top.compare_and_swap(t, t + 1, Ordering::SeqCst) != t {
+            mem::forget(val);
+            return Steal::Abort;
+        }
+
+        Steal::Data(val)
+    }
+
+    // Copies the values into a buffer twice the size and retires the old one.
+    // Owner only.
+    unsafe fn grow(&self, old: *mut Buffer<T>, b: isize, t: isize) -> *mut Buffer<T> {
+        let new = Buffer::new((*old).capacity() * 2);
+
+        for i in range(t, b) {
+            (*new).write(i, (*old).read(i));
+        }
+
+        self.buffer.store(new, Ordering::SeqCst);
+
+        self.retired.lock()
+            .ok().expect("something went wrong")
+            .push(old);
+
+        new
+    }
+}
+
+#[unsafe_destructor]
+impl<T: Send> Drop for DequeInner<T> {
+    fn drop(&mut self) {
+        unsafe {
+            while let Some(_) = self.pop() {
+            }
+
+            Buffer::free(self.buffer.load(Ordering::Relaxed));
+
+            let retired = self.retired.lock()
+                .ok().expect("something went wrong");
+
+            for &buf in retired.iter() {
+                Buffer::free(buf);
+            }
+        }
+    }
+}
+
+const MIN_CAPACITY: usize = 16;
+
+// A circular buffer of uninitialized slots. Values are moved in and out
+// bitwise, dropping the buffer does not drop any values.
+struct Buffer<T> {
+    ptr: *mut T,
+    capacity: usize,
+}
+
+impl<T: Send> Buffer<T> {
+    // The capacity must be a power of two
+    fn new(capacity: usize) -> *mut Buffer<T> {
+        let mut storage: Vec<T> = Vec::with_capacity(capacity);
+        let ptr = storage.as_mut_ptr();
+
+        unsafe {
+            mem::forget(storage);
+            mem::transmute(Box::new(Buffer { ptr: ptr, capacity: capacity }))
+        }
+    }
+
+    unsafe fn free(buf: *mut Buffer<T>) {
+        let buf: Box<Buffer<T>> = mem::transmute(buf);
+        let _: Vec<T> = Vec::from_raw_parts(buf.ptr, 0, buf.capacity);
+    }
+
+    fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    unsafe fn read(&self, i: isize) -> T {
+        ptr::read(self.slot(i))
+    }
+
+    unsafe fn write(&self, i: isize, val: T) {
+        ptr::write(self.slot(i), val);
+    }
+
+    unsafe fn slot(&self, i: isize) -> *mut T {
+        self.ptr.offset(i & (self.capacity as isize - 1))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Deque, Steal};
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::mpsc::channel;
+    use std::thread::Thread;
+
+    #[test]
+    pub fn test_single_threaded_push_pop() {
+        let mut deque = Deque::new();
+
+        assert!(deque.is_empty());
+        assert!(deque.pop().is_none());
+
+        for i in range(0, 3u) {
+            deque.push(i);
+        }
+
+        assert_eq!(3, deque.len());
+
+        // Values are popped in LIFO order
+        assert_eq!(Some(2), deque.pop());
+        assert_eq!(Some(1), deque.pop());
+        assert_eq!(Some(0), deque.pop());
+        assert!(deque.pop().is_none());
+    }
+
+    #[test]
+    pub fn test_steal_takes_from_top() {
+        let mut deque = Deque::new();
+        let stealer = deque.stealer();
+
+        assert_eq!(Steal::Empty, stealer.steal());
+
+        for i in range(0, 3u) {
+            deque.push(i);
+        }
+
+        // Values are stolen in FIFO order
+        assert_eq!(Steal::Data(0), stealer.steal());
+        assert_eq!(Some(2), deque.pop());
+        assert_eq!(Steal::Data(1), stealer.steal());
+        assert_eq!(Steal::Empty, stealer.steal());
+    }
+
+    #[test]
+    pub fn test_push_grows_buffer() {
+        let mut deque = Deque::new();
+        let stealer = deque.stealer();
+
+        for i in range(0, 1_000u) {
+            deque.push(i);
+        }
+
+        assert_eq!(1_000, deque.len());
+        assert_eq!(Steal::Data(0), stealer.steal());
+        assert_eq!(Some(999), deque.pop());
+    }
+
+    #[test]
+    pub fn test_multi_threaded_steal_race() {
+        const VALS: uint = 10_000;
+        const STEALERS: uint = 4;
+
+        let mut deque = Deque::new();
+        let count = Arc::new(AtomicUsize::new(0));
+        let sum = Arc::new(AtomicUsize::new(0));
+        let (tx, rx) = channel();
+
+        for i in range(0, VALS) {
+            deque.push(i);
+        }
+
+        for _ in range(0, STEALERS) {
+            let stealer = deque.stealer();
+            let (count, sum, tx) = (count.clone(), sum.clone(), tx.clone());
+
+            Thread::spawn(move || {
+                loop {
+                    match stealer.steal() {
+                        Steal::Data(v) => {
+                            count.fetch_add(1, Ordering::SeqCst);
+                            sum.fetch_add(v, Ordering::SeqCst);
+                        }
+                        Steal::Abort => {}
+                        Steal::Empty => break,
+                    }
+                }
+
+                tx.send(()).unwrap();
+            });
+        }
+
+        // The owner pops concurrently with the stealers
+        while let Some(v) = deque.pop() {
+            count.fetch_add(1, Ordering::SeqCst);
+            sum.fetch_add(v, Ordering::SeqCst);
+        }
+
+        for _ in range(0, STEALERS) {
+            rx.recv().unwrap();
+        }
+
+        // Every value was taken exactly once
+        assert_eq!(VALS, count.load(Ordering::SeqCst));
+        assert_eq!(VALS * (VALS - 1) / 2, sum.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    pub fn test_drop_with_values() {
+        let mut deque = Deque::new();
+
+        for _ in range(0, 100u) {
+            deque.push(Box::new(1u));
+        }
+
+        drop(deque);
+    }
+}
@@ -1,8 +1,9 @@
-use super::{Queue, SyncQueue};
-use std::{mem, ptr, ops, usize};
+use super::{Queue, SyncQueue, deadline, wait_until};
+use std::{cmp, mem, ptr, ops, usize};
 use std::sync::{Arc, Mutex, MutexGuard, Condvar};
-use std::sync::atomic::{self, AtomicUsize, Ordering};
+use std::sync::atomic::{self, AtomicBool, AtomicUsize, Ordering};
 use std::time::Duration;
+use time::SteadyTime;
 
 /// A queue in which values are contained by a linked list.
 ///
@@ -24,6 +25,26 @@ impl<T: Send> LinkedQueue<T> {
         }
     }
 
+    /// Returns the maximum number of elements the queue can contain, or None
+    /// if the queue is unbounded.
+    pub fn capacity(&self) -> Option<usize> {
+        let capacity = self.inner.capacity();
+
+        if capacity == usize::MAX {
+            return None;
+        }
+
+        Some(capacity)
+    }
+
+    /// Changes the maximum number of elements the queue can contain. Growing
+    /// the capacity wakes up blocked producers. Shrinking it below the
+    /// current length never drops elements, producers are instead blocked
+    /// until enough elements have been taken.
+    pub fn set_capacity(&self, capacity: usize) {
+        self.inner.set_capacity(capacity);
+    }
+
     pub fn len(&self) -> usize {
         self.inner.len()
     }
@@ -32,22 +53,137 @@ impl<T: Send> LinkedQueue<T> {
         self.len() == 0
     }
 
+    /// Inserts the element into the queue if there is space available. The
+    /// element is returned if the queue is at capacity or closed.
     pub fn offer(&self, e: T) -> Result<(), T> {
         self.inner.offer(e)
     }
 
+    /// Inserts the element into the queue if there is space available. Unlike
+    /// `offer`, the error reports whether the queue was at capacity or
+    /// closed.
+    pub fn try_put(&self, e: T) -> Result<(), TryPutError<T>> {
+        self.inner.offer(e).map_err(|e| {
+            // The closed flag is never unset, so checking it after the fact
+            // is enough to tell the two failures apart
+            if self.is_closed() {
+                TryPutError::Closed(e)
+            } else {
+                TryPutError::Full(e)
+            }
+        })
+    }
+
+    /// Inserts the element into the queue, waiting up to `timeout` for space
+    /// to become available. The element is returned if the queue is still at
+    /// capacity once the timeout elapses, or if the queue is closed.
+    pub fn offer_timeout(&self, e: T, timeout: Duration) -> Result<(), T> {
+        self.inner.offer_for(e, timeout)
+    }
+
+    /// Inserts into the queue, blocking until there is space available.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the queue is closed.
     pub fn put(&self, e: T) {
         self.inner.put(e);
     }
 
+    /// Inserts all of the elements into the queue, in order. As many elements
+    /// as fit are inserted under a single lock acquisition, blocking until
+    /// there is space available for the rest.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the queue is closed.
+    pub fn put_batch(&self, es: Vec<T>) {
+        self.inner.put_batch(es)
+            .ok().expect("put on a closed queue");
+    }
+
     pub fn poll(&self) -> Option<T> {
         self.inner.poll()
     }
 
+    /// Takes from the queue, waiting up to `timeout` for an element to become
+    /// available. Returns None if the queue is still empty once the timeout
+    /// elapses.
+    pub fn poll_timeout(&self, timeout: Duration) -> Option<T> {
+        self.inner.poll_for(timeout)
+    }
+
     /// Takes from the queue, blocking until there is an element available.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the queue is closed and empty. Use `take_or_closed` when the
+    /// queue may be closed.
     pub fn take(&self) -> T {
         self.inner.take()
     }
+
+    /// Takes from the queue, blocking until there is an element available.
+    /// Returns None once the queue is closed and all remaining elements have
+    /// been taken.
+    pub fn take_or_closed(&self) -> Option<T> {
+        self.inner.poll_for(Duration::max_value())
+    }
+
+    /// Closes the queue, waking up all blocked takers and producers. Elements
+    /// already in the queue can still be taken, after which takers are
+    /// notified that the queue is closed instead of blocking. Elements can no
+    /// longer be inserted.
+    pub fn close(&self) {
+        self.inner.close();
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.inner.is_closed()
+    }
+
+    /// Takes up to `max` elements from the queue, blocking until at least one
+    /// is available. All of the elements are taken under a single lock
+    /// acquisition. Returns an empty vector once the queue is closed and all
+    /// remaining elements have been taken.
+    pub fn take_batch(&self, max: usize) -> Vec<T> {
+        self.inner.take_batch(max)
+    }
+
+    /// Removes all elements currently in the queue without blocking,
+    /// returning them in queue order.
+    pub fn drain(&self) -> Vec<T> {
+        self.inner.drain()
+    }
+
+    /// Removes and drops all elements currently in the queue.
+    pub fn clear(&self) {
+        self.inner.clear();
+    }
+}
+
+impl<T: Send + Clone> LinkedQueue<T> {
+    /// Returns a clone of the head of the queue without removing it, or None
+    /// if the queue is empty.
+    ///
+    /// Unlike the rest of the queue API, which moves values in and out of the
+    /// queue, peeking requires the element type to be `Clone` since the
+    /// element stays in the queue.
+    pub fn peek(&self) -> Option<T> {
+        self.inner.peek()
+    }
+}
+
+impl<T: Send + PartialEq> LinkedQueue<T> {
+    /// Removes the first element equal to `item` from the queue. Returns true
+    /// if an element was removed.
+    ///
+    /// This is useful to withdraw values that have been queued but not yet
+    /// taken. Both the put and the take locks are held while searching the
+    /// queue, so this operation blocks all other queue operations.
+    pub fn remove(&self, item: &T) -> bool {
+        self.inner.remove(item)
+    }
 }
 
 impl<T: Send> Queue<T> for LinkedQueue<T> {
@@ -55,6 +191,14 @@ impl<T: Send> Queue<T> for LinkedQueue<T> {
         LinkedQueue::poll(self)
     }
 
+    fn len(&self) -> usize {
+        LinkedQueue::len(self)
+    }
+
+    fn capacity(&self) -> Option<usize> {
+        LinkedQueue::capacity(self)
+    }
+
     fn is_empty(&self) -> bool {
         LinkedQueue::is_empty(self)
     }
@@ -80,6 +224,26 @@ impl<T: Send> Clone for LinkedQueue<T> {
     }
 }
 
+/// The error returned by `LinkedQueue::try_put`. Both variants hand back the
+/// element that could not be inserted.
+#[derive(Debug, PartialEq, Eq)]
+pub enum TryPutError<T> {
+    /// The queue is at capacity
+    Full(T),
+    /// The queue has been closed
+    Closed(T),
+}
+
+impl<T> TryPutError<T> {
+    /// Returns the element that could not be inserted.
+    pub fn into_inner(self) -> T {
+        match self {
+            TryPutError::Full(e) => e,
+            TryPutError::Closed(e) => e,
+        }
+    }
+}
+
 //  A variant of the "two lock queue" algorithm.  The putLock gates
 //  entry to put (and offer), and has an associated condition for
 //  waiting puts.  Similarly for the takeLock.  The "count" field
@@ -114,8 +278,9 @@ impl<T: Send> Clone for LinkedQueue<T> {
 //  self-link implicitly means to advance to head.next.
 struct QueueInner<T> {
 
-    // Maximum number of elements the queue can contain at one time
-    capacity: usize,
+    // Maximum number of elements the queue can contain at one time. Only
+    // changed while holding both locks.
+    capacity: AtomicUsize,
 
     // Current number of elements
     count: AtomicUsize,
@@ -131,6 +296,9 @@ struct QueueInner<T> {
 
     // Wait queue for waiting puts
     not_full: Condvar,
+
+    // True once the queue has been closed
+    closed: AtomicBool,
 }
 
 impl<T: Send> QueueInner<T> {
@@ -138,12 +306,13 @@ impl<T: Send> QueueInner<T> {
         let head = NodePtr::new(Node::empty());
 
         QueueInner {
-            capacity: capacity,
+            capacity: AtomicUsize::new(capacity),
             count: AtomicUsize::new(0),
             head: Mutex::new(head),
             last: Mutex::new(head),
             not_empty: Condvar::new(),
             not_full: Condvar::new(),
+            closed: AtomicBool::new(false),
         }
     }
 
@@ -151,13 +320,96 @@ impl<T: Send> QueueInner<T> {
         self.count.load(Ordering::Relaxed)
     }
 
+    fn capacity(&self) -> usize {
+        self.capacity.load(Ordering::Relaxed)
+    }
+
+    fn set_capacity(&self, capacity: usize) {
+        // Hold both locks so that takers observe the new capacity when
+        // deciding whether to notify producers.
+        let (last, head) = self.fully_lock();
+
+        let prev = self.capacity.swap(capacity, Ordering::Relaxed);
+
+        if capacity > prev {
+            self.not_full.notify_all();
+        }
+
+        drop(head);
+        drop(last);
+    }
+
+    fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::Relaxed)
+    }
+
+    fn close(&self) {
+        // Set the flag while holding both locks so that neither a taker nor a
+        // producer can check the flag and then miss the notification.
+        let (last, head) = self.fully_lock();
+
+        self.closed.store(true, Ordering::Relaxed);
+
+        self.not_empty.notify_all();
+        self.not_full.notify_all();
+
+        drop(head);
+        drop(last);
+    }
+
     fn put(&self, e: T) {
         self.offer_for(e, Duration::max_value())
+            .ok().expect("put on a closed queue");
+    }
+
+    // Enqueues the values in chunks, each one filling the queue up to its
+    // capacity under a single acquisition of the write lock. Returns the
+    // values that were not enqueued if the queue is closed.
+    fn put_batch(&self, vals: Vec<T>) -> Result<(), Vec<T>> {
+        let mut vals = vals.into_iter().peekable();
+
+        // Acquire the write lock
+        let mut last = self.last.lock()
             .ok().expect("something went wrong");
+
+        while vals.peek().is_some() {
+            while self.len() >= self.capacity() && !self.is_closed() {
+                last = self.not_full.wait(last)
+                    .ok().expect("something went wrong");
+            }
+
+            if self.is_closed() {
+                return Err(vals.collect());
+            }
+
+            // Only producers increment the count and the write lock is held,
+            // so there is room for at least this many values
+            let room = self.capacity() - self.len();
+            let mut n = 0;
+
+            for e in vals.by_ref().take(room) {
+                enqueue(Node::new(e), &mut last);
+                n += 1;
+            }
+
+            // Increment the count once for the whole chunk
+            let cnt = self.count.fetch_add(n, Ordering::Release);
+
+            if cnt + n < self.capacity() {
+                self.not_full.notify_one();
+            }
+
+            // Takers cascade the notification if more values are available.
+            // The read lock is always acquired after the write lock, so this
+            // cannot deadlock.
+            self.notify_not_empty();
+        }
+
+        Ok(())
     }
 
     fn offer(&self, e: T) -> Result<(), T> {
-        if self.len() == self.capacity {
+        if self.len() >= self.capacity() || self.is_closed() {
             return Err(e);
         }
 
@@ -165,17 +417,25 @@ impl<T: Send> QueueInner<T> {
     }
 
     fn offer_for(&self, e: T, dur: Duration) -> Result<(), T> {
+        let deadline = deadline(dur);
+
         // Acquire the write lock
         let mut last = self.last.lock()
             .ok().expect("something went wrong");
 
-        while self.len() == self.capacity {
-            if dur.num_milliseconds() <= 0 {
+        loop {
+            if self.is_closed() {
                 return Err(e);
             }
 
-            last = self.not_full.wait(last)
-                .ok().expect("something went wrong");
+            if self.len() < self.capacity() {
+                break;
+            }
+
+            last = match wait_until(&self.not_full, last, deadline) {
+                Some(last) => last,
+                None => return Err(e),
+            };
         }
 
         // Enqueue the node
@@ -184,7 +444,7 @@ impl<T: Send> QueueInner<T> {
         // Increment the count
         let cnt = self.count.fetch_add(1, Ordering::Release);
 
-        if cnt + 1 < self.capacity {
+        if cnt + 1 < self.capacity() {
             self.not_full.notify_one();
         }
 
@@ -197,7 +457,7 @@ impl<T: Send> QueueInner<T> {
 
     fn take(&self) -> T {
         self.poll_for(Duration::max_value())
-            .expect("something went wrong")
+            .expect("take on a closed and empty queue")
     }
 
     fn poll(&self) -> Option<T> {
@@ -211,17 +471,10 @@ impl<T: Send> QueueInner<T> {
 
     fn poll_for(&self, dur: Duration) -> Option<T> {
         // Acquire the read lock
-        let mut head = self.head.lock()
-            .ok().expect("something went wrong");
-
-        while self.len() == 0 {
-            if dur.num_milliseconds() <= 0 {
-                return None;
-            }
-
-            head = self.not_empty.wait(head)
-                .ok().expect("something went wrong");
-        }
+        let mut head = match self.wait_not_empty(deadline(dur)) {
+            Some(head) => head,
+            None => return None,
+        };
 
         // Acquire memory from write side
         atomic::fence(Ordering::Acquire);
@@ -238,13 +491,118 @@ impl<T: Send> QueueInner<T> {
         // in a deadlock
         drop(head);
 
-        if cnt == self.capacity {
+        if cnt == self.capacity() {
             self.notify_not_full();
         }
 
         Some(val)
     }
 
+    fn take_batch(&self, max: usize) -> Vec<T> {
+        if max == 0 {
+            return vec![];
+        }
+
+        // Acquire the read lock
+        let mut head = match self.wait_not_empty(None) {
+            Some(head) => head,
+            None => return vec![],
+        };
+
+        // Acquire memory from write side
+        atomic::fence(Ordering::Acquire);
+
+        // Only the takers decrement the count and the read lock is held, so
+        // at least `n` values can be dequeued
+        let n = cmp::min(self.len(), max);
+        let mut ret = Vec::with_capacity(n);
+
+        for _ in range(0, n) {
+            ret.push(dequeue(&mut head));
+        }
+
+        let cnt = self.count.fetch_sub(n, Ordering::Relaxed);
+
+        if cnt > n {
+            self.not_empty.notify_one();
+        }
+
+        drop(head);
+
+        if cnt >= self.capacity() && cnt - n < self.capacity() {
+            // Producers cascade the notification if there is room for more
+            self.notify_not_full();
+        }
+
+        ret
+    }
+
+    // Acquires the read lock and waits until the queue is not empty. Returns
+    // None if the deadline is reached, or if the queue is closed while empty.
+    fn wait_not_empty(&self, deadline: Option<SteadyTime>) -> Option<MutexGuard<NodePtr<T>>> {
+        let mut head = self.head.lock()
+            .ok().expect("something went wrong");
+
+        while self.len() == 0 {
+            if self.is_closed() {
+                return None;
+            }
+
+            head = match wait_until(&self.not_empty, head, deadline) {
+                Some(head) => head,
+                None => return None,
+            };
+        }
+
+        Some(head)
+    }
+
+    fn drain(&self) -> Vec<T> {
+        let mut ret = Vec::with_capacity(self.len());
+        self.dequeue_all(|val| ret.push(val));
+        ret
+    }
+
+    fn clear(&self) {
+        self.dequeue_all(|_| {});
+    }
+
+    // Dequeues every element while holding both locks, passing each one to
+    // the supplied function. Dequeuing leaves the last node in place as the
+    // new sentinel head, so `head` and `last` end up pointing to the same
+    // empty node and the queue remains usable.
+    fn dequeue_all<F: FnMut(T)>(&self, mut f: F) {
+        let (last, mut head) = self.fully_lock();
+
+        // Both locks are held, so the count cannot change concurrently
+        let cnt = self.count.swap(0, Ordering::Relaxed);
+
+        for _ in range(0, cnt) {
+            f(dequeue(&mut head));
+        }
+
+        if cnt >= self.capacity() {
+            // Producers may be waiting, cascading notifies will wake the rest
+            self.not_full.notify_one();
+        }
+
+        drop(head);
+        drop(last);
+    }
+
+    // Acquires both the put and the take locks, preventing any concurrent
+    // modification of the queue. The locks are always acquired in the same
+    // order to avoid deadlocks.
+    fn fully_lock(&self) -> (MutexGuard<NodePtr<T>>, MutexGuard<NodePtr<T>>) {
+        let last = self.last.lock()
+            .ok().expect("something went wrong");
+
+        let head = self.head.lock()
+            .ok().expect("something went wrong");
+
+        (last, head)
+    }
+
     // Signals a waiting put. Called only from take / poll
     fn notify_not_full(&self) {
         let _l = self.last.lock()
@@ -269,6 +627,64 @@ impl<T: Send> Drop for QueueInner<T> {
     }
 }
 
+impl<T: Send + Clone> QueueInner<T> {
+    fn peek(&self) -> Option<T> {
+        if self.len() == 0 {
+            // Fast path check
+            return None;
+        }
+
+        // Acquire the read lock, this prevents the head from being dequeued
+        let head = self.head.lock()
+            .ok().expect("something went wrong");
+
+        if self.len() == 0 {
+            return None;
+        }
+
+        // Acquire memory from write side
+        atomic::fence(Ordering::Acquire);
+
+        head.next.item.clone()
+    }
+}
+
+impl<T: Send + PartialEq> QueueInner<T> {
+    fn remove(&self, item: &T) -> bool {
+        let (mut last, head) = self.fully_lock();
+
+        let mut trail = *head;
+        let mut curr = trail.next;
+
+        while !curr.is_null() {
+            if curr.item.as_ref().map_or(false, |v| v == item) {
+                // Unlink the node
+                trail.next = curr.next;
+
+                if last.ptr == curr.ptr {
+                    **last = trail;
+                }
+
+                curr.free();
+
+                let cnt = self.count.fetch_sub(1, Ordering::Relaxed);
+
+                if cnt == self.capacity() {
+                    // The put lock is held, signal a waiting producer
+                    self.not_full.notify_one();
+                }
+
+                return true;
+            }
+
+            trail = curr;
+            curr = curr.next;
+        }
+
+        false
+    }
+}
+
 fn dequeue<T: Send>(mut head: &mut MutexGuard<NodePtr<T>>) -> T {
     let h = **head;
     let mut first = h.next;
@@ -318,6 +734,10 @@ impl<T: Send> NodePtr<T> {
         NodePtr { ptr: ptr::null_mut() }
     }
 
+    fn is_null(&self) -> bool {
+        self.ptr.is_null()
+    }
+
     fn free(self) {
         let NodePtr { ptr } = self;
         let _: Box<Node<T>> = unsafe { mem::transmute(ptr) };
@@ -343,10 +763,12 @@ unsafe impl<T: Send> Send for NodePtr<T> {}
 
 #[cfg(test)]
 mod test {
-    use super::LinkedQueue;
+    use super::{LinkedQueue, TryPutError};
+    use util::Queue;
     use std::old_io::timer::sleep;
     use std::time::Duration;
     use std::thread::Thread;
+    use std::sync::mpsc::sync_channel;
 
     #[test]
     pub fn test_single_threaded_put_take() {
@@ -529,6 +951,462 @@ mod test {
         }
     }
 
+    #[test]
+    pub fn test_offer_timeout_elapses() {
+        let queue = LinkedQueue::with_capacity(1);
+
+        assert!(queue.offer(1u).is_ok());
+        assert_eq!(Err(2), queue.offer_timeout(2, millis(20)));
+        assert_eq!(1, queue.len());
+    }
+
+    #[test]
+    pub fn test_offer_timeout_unblocked_by_poll() {
+        let queue = LinkedQueue::with_capacity(1);
+        let consumer = queue.clone();
+
+        assert!(queue.offer(1u).is_ok());
+
+        Thread::spawn(move || {
+            sleep(millis(20));
+            assert_eq!(Some(1), consumer.poll());
+        });
+
+        assert!(queue.offer_timeout(2, millis(10_000)).is_ok());
+        assert_eq!(Some(2), queue.poll());
+    }
+
+    #[test]
+    pub fn test_poll_timeout_elapses() {
+        let queue: LinkedQueue<uint> = LinkedQueue::new();
+        assert!(queue.poll_timeout(millis(20)).is_none());
+    }
+
+    #[test]
+    pub fn test_poll_timeout_unblocked_by_put() {
+        let queue = LinkedQueue::new();
+        let producer = queue.clone();
+
+        Thread::spawn(move || {
+            sleep(millis(20));
+            producer.put(1u);
+            producer.put(2u);
+        });
+
+        assert_eq!(Some(1), queue.poll_timeout(millis(10_000)));
+        assert_eq!(Some(2), queue.poll_timeout(millis(10_000)));
+        assert_eq!(0, queue.len());
+    }
+
+    #[test]
+    pub fn test_drain_unblocks_producer() {
+        let queue = LinkedQueue::with_capacity(4);
+        let producer = queue.clone();
+
+        for i in range(0, 4u) {
+            queue.put(i);
+        }
+
+        let (tx, rx) = sync_channel(0);
+
+        Thread::spawn(move || {
+            // Blocks until the queue is drained
+            producer.put(4);
+            tx.send(()).unwrap();
+        });
+
+        sleep(millis(20));
+
+        assert_eq!(vec![0, 1, 2, 3], queue.drain());
+
+        rx.recv().unwrap();
+        assert_eq!(vec![4], queue.drain());
+        assert!(queue.is_empty());
+        assert!(queue.drain().is_empty());
+    }
+
+    #[test]
+    pub fn test_peek() {
+        let queue = LinkedQueue::new();
+
+        assert!(queue.peek().is_none());
+
+        queue.put(1u);
+        queue.put(2u);
+
+        assert_eq!(Some(1), queue.peek());
+        assert_eq!(Some(1), queue.peek());
+        assert_eq!(2, queue.len());
+
+        assert_eq!(1, queue.take());
+        assert_eq!(Some(2), queue.peek());
+        assert_eq!(2, queue.take());
+
+        assert!(queue.peek().is_none());
+    }
+
+    #[test]
+    pub fn test_clear_then_round_trip() {
+        let queue = LinkedQueue::with_capacity(8);
+
+        for i in range(0, 4u) {
+            queue.put(i);
+        }
+
+        queue.clear();
+
+        assert_eq!(0, queue.len());
+        assert!(queue.poll().is_none());
+
+        queue.put(123);
+        assert_eq!(1, queue.len());
+        assert_eq!(123, queue.take());
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    pub fn test_capacity() {
+        let unbounded: LinkedQueue<uint> = LinkedQueue::new();
+        let bounded: LinkedQueue<uint> = LinkedQueue::with_capacity(8);
+
+        assert_eq!(None, unbounded.capacity());
+        assert_eq!(Some(8), bounded.capacity());
+    }
+
+    #[test]
+    pub fn test_len_and_capacity_through_trait() {
+        fn remaining<Q: Queue<uint>>(q: &Q) -> Option<uint> {
+            q.capacity().map(|cap| cap - q.len())
+        }
+
+        let queue = LinkedQueue::with_capacity(8);
+
+        queue.put(1u);
+        queue.put(2u);
+
+        assert_eq!(Some(6), remaining(&queue));
+        assert_eq!(None, remaining(&LinkedQueue::new()));
+    }
+
+    #[test]
+    pub fn test_take_or_closed_after_close() {
+        let queue = LinkedQueue::new();
+
+        queue.put(1u);
+        queue.put(2u);
+        queue.close();
+
+        assert!(queue.is_closed());
+
+        // Remaining elements can still be taken
+        assert_eq!(Some(1), queue.take_or_closed());
+        assert_eq!(Some(2), queue.take_or_closed());
+        assert_eq!(None, queue.take_or_closed());
+    }
+
+    #[test]
+    pub fn test_close_unblocks_taker() {
+        let queue: LinkedQueue<uint> = LinkedQueue::new();
+        let q = queue.clone();
+        let (tx, rx) = sync_channel(0);
+
+        Thread::spawn(move || {
+            tx.send(q.take_or_closed()).unwrap();
+        });
+
+        sleep(millis(20));
+        assert!(rx.try_recv().is_err());
+
+        queue.close();
+        assert_eq!(None, rx.recv().unwrap());
+    }
+
+    #[test]
+    pub fn test_close_unblocks_producer() {
+        let queue = LinkedQueue::with_capacity(1);
+        let q = queue.clone();
+        let (tx, rx) = sync_channel(0);
+
+        queue.put(1u);
+
+        Thread::spawn(move || {
+            tx.send(q.offer_timeout(2u, Duration::max_value())).unwrap();
+        });
+
+        sleep(millis(20));
+        assert!(rx.try_recv().is_err());
+
+        queue.close();
+        assert_eq!(Err(2), rx.recv().unwrap());
+
+        // The element inserted before closing can still be taken
+        assert_eq!(1, queue.take());
+    }
+
+    #[test]
+    pub fn test_offer_after_close() {
+        let queue = LinkedQueue::new();
+
+        queue.close();
+
+        assert_eq!(Err(1u), queue.offer(1u));
+        assert_eq!(None, queue.poll());
+    }
+
+    #[test]
+    #[should_fail(expected = "take on a closed and empty queue")]
+    pub fn test_take_after_close_panics() {
+        let queue: LinkedQueue<uint> = LinkedQueue::new();
+
+        queue.close();
+        queue.take();
+    }
+
+    #[test]
+    pub fn test_remove_middle_element() {
+        let queue = LinkedQueue::new();
+
+        for i in range(0, 3u) {
+            queue.put(i);
+        }
+
+        assert!(queue.remove(&1));
+        assert_eq!(2, queue.len());
+        assert_eq!(vec![0, 2], queue.drain());
+    }
+
+    #[test]
+    pub fn test_remove_tail_element() {
+        let queue = LinkedQueue::new();
+
+        for i in range(0, 3u) {
+            queue.put(i);
+        }
+
+        assert!(queue.remove(&2));
+        assert_eq!(2, queue.len());
+
+        // The queue is still usable after the tail is removed
+        queue.put(3);
+        assert_eq!(vec![0, 1, 3], queue.drain());
+    }
+
+    #[test]
+    pub fn test_remove_missing_element() {
+        let queue = LinkedQueue::new();
+
+        queue.put(1u);
+
+        assert!(!queue.remove(&2));
+        assert_eq!(1, queue.len());
+        assert_eq!(Some(1), queue.poll());
+
+        // Removing from an empty queue
+        assert!(!queue.remove(&1));
+    }
+
+    #[test]
+    pub fn test_remove_unblocks_producer() {
+        let queue = LinkedQueue::with_capacity(1);
+        let (tx, rx) = sync_channel(1);
+
+        queue.put(1u);
+
+        {
+            let queue = queue.clone();
+
+            Thread::spawn(move || {
+                queue.put(2);
+                tx.send(()).unwrap();
+            });
+        }
+
+        sleep(millis(20));
+        assert!(rx.try_recv().is_err());
+
+        assert!(queue.remove(&1));
+        rx.recv().unwrap();
+
+        assert_eq!(Some(2), queue.poll());
+    }
+
+    #[test]
+    pub fn test_put_batch() {
+        let queue = LinkedQueue::new();
+
+        queue.put_batch(range(0, 100u).collect());
+        assert_eq!(100, queue.len());
+
+        let vals: Vec<uint> = range(0, 100).map(|_| queue.take()).collect();
+        assert_eq!(range(0, 100u).collect::<Vec<uint>>(), vals);
+    }
+
+    #[test]
+    pub fn test_put_batch_at_capacity() {
+        let queue = LinkedQueue::with_capacity(3);
+
+        {
+            let queue = queue.clone();
+
+            Thread::spawn(move || {
+                queue.put_batch(range(0, 10u).collect());
+            });
+        }
+
+        sleep(millis(20));
+
+        // The values that fit were enqueued before blocking
+        assert_eq!(3, queue.len());
+
+        let vals: Vec<uint> = range(0, 10).map(|_| queue.take()).collect();
+        assert_eq!(range(0, 10u).collect::<Vec<uint>>(), vals);
+    }
+
+    #[test]
+    #[should_fail(expected = "put on a closed queue")]
+    pub fn test_put_batch_after_close_panics() {
+        let queue = LinkedQueue::new();
+
+        queue.close();
+        queue.put_batch(vec![1u, 2]);
+    }
+
+    #[test]
+    pub fn test_take_batch() {
+        let queue = LinkedQueue::new();
+
+        for i in range(0, 5u) {
+            queue.put(i);
+        }
+
+        assert_eq!(vec![0, 1, 2], queue.take_batch(3));
+        assert_eq!(2, queue.len());
+
+        // Takes only what is available
+        assert_eq!(vec![3, 4], queue.take_batch(3));
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    pub fn test_take_batch_blocks_until_available() {
+        let queue = LinkedQueue::new();
+
+        {
+            let queue = queue.clone();
+
+            Thread::spawn(move || {
+                sleep(millis(20));
+                queue.put(1u);
+            });
+        }
+
+        assert_eq!(vec![1], queue.take_batch(3));
+    }
+
+    #[test]
+    pub fn test_take_batch_unblocks_producer() {
+        let queue = LinkedQueue::with_capacity(2);
+        let (tx, rx) = sync_channel(1);
+
+        queue.put(1u);
+        queue.put(2u);
+
+        {
+            let queue = queue.clone();
+
+            Thread::spawn(move || {
+                queue.put(3);
+                tx.send(()).unwrap();
+            });
+        }
+
+        assert_eq!(vec![1, 2], queue.take_batch(5));
+        rx.recv().unwrap();
+
+        assert_eq!(Some(3), queue.poll());
+    }
+
+    #[test]
+    pub fn test_take_batch_after_close() {
+        let queue = LinkedQueue::new();
+
+        queue.put(1u);
+        queue.close();
+
+        assert_eq!(vec![1], queue.take_batch(3));
+        assert!(queue.take_batch(3).is_empty());
+    }
+
+    #[test]
+    pub fn test_set_capacity_grow_unblocks_producer() {
+        let queue = LinkedQueue::with_capacity(2);
+        let (tx, rx) = sync_channel(1);
+
+        queue.put(1u);
+        queue.put(2u);
+
+        {
+            let queue = queue.clone();
+
+            Thread::spawn(move || {
+                queue.put(3);
+                tx.send(()).unwrap();
+            });
+        }
+
+        sleep(millis(50));
+        assert!(rx.try_recv().is_err());
+
+        queue.set_capacity(3);
+        rx.recv().unwrap();
+
+        assert_eq!(Some(3), queue.capacity());
+        assert_eq!(vec![1, 2, 3], queue.drain());
+    }
+
+    #[test]
+    pub fn test_set_capacity_shrink_keeps_elements() {
+        let queue = LinkedQueue::with_capacity(4);
+
+        for i in range(0, 4u) {
+            queue.put(i);
+        }
+
+        queue.set_capacity(2);
+
+        assert_eq!(4, queue.len());
+        assert!(queue.offer(4).is_err());
+
+        assert_eq!(Some(0), queue.poll());
+        assert_eq!(Some(1), queue.poll());
+        assert!(queue.offer(4).is_err());
+
+        assert_eq!(Some(2), queue.poll());
+        assert!(queue.offer(4).is_ok());
+        assert_eq!(vec![3, 4], queue.drain());
+    }
+
+    #[test]
+    pub fn test_try_put_full() {
+        let queue = LinkedQueue::with_capacity(1);
+
+        assert_eq!(Ok(()), queue.try_put(1u));
+        assert_eq!(Err(TryPutError::Full(2)), queue.try_put(2));
+
+        assert_eq!(Some(1), queue.poll());
+        assert_eq!(Ok(()), queue.try_put(3));
+    }
+
+    #[test]
+    pub fn test_try_put_closed() {
+        let queue = LinkedQueue::with_capacity(1);
+
+        queue.close();
+
+        assert_eq!(Err(TryPutError::Closed(1u)), queue.try_put(1));
+        assert_eq!(1, queue.try_put(1).unwrap_err().into_inner());
+    }
+
     fn millis(num: uint) -> Duration {
         Duration::milliseconds(num as i64)
     }
@@ -1,11 +1,65 @@
-pub use self::linked_queue::LinkedQueue;
+pub use self::array_queue::ArrayQueue;
+pub use self::broadcast_queue::{BroadcastQueue, Subscriber};
+pub use self::count_down_latch::CountDownLatch;
+pub use self::deque::{Deque, Stealer, Steal};
+pub use self::linked_queue::{LinkedQueue, TryPutError};
 pub use self::thread_pool::ThreadPool;
 pub use self::queue::{Queue, SyncQueue};
 pub use self::run::Run;
+pub use self::semaphore::Semaphore;
+pub use self::spsc_queue::{spsc_queue, Producer, Consumer};
+
+use std::sync::{Condvar, MutexGuard};
+use std::time::Duration;
+use time::SteadyTime;
 
 pub mod async;
 pub mod atomic;
+mod array_queue;
+mod broadcast_queue;
+mod count_down_latch;
+mod deque;
 mod linked_queue;
 mod thread_pool;
 mod queue;
 mod run;
+mod semaphore;
+mod spsc_queue;
+
+// Returns the point in time at which a timed operation gives up, or None if
+// the operation should wait indefinitely.
+fn deadline(dur: Duration) -> Option<SteadyTime> {
+    if dur == Duration::max_value() {
+        return None;
+    }
+
+    Some(SteadyTime::now() + dur)
+}
+
+// Waits on the condition variable until it is notified. Returns None if the
+// deadline is reached first. The remaining time is recomputed on each call,
+// so callers waiting in a loop handle spurious wakeups.
+fn wait_until<'a, T>(condvar: &Condvar,
+                     guard: MutexGuard<'a, T>,
+                     deadline: Option<SteadyTime>) -> Option<MutexGuard<'a, T>> {
+    match deadline {
+        Some(deadline) => {
+            let now = SteadyTime::now();
+
+            if now >= deadline {
+                return None;
+            }
+
+            let guard = condvar.wait_timeout(guard, deadline - now)
+                .ok().expect("something went wrong").0;
+
+            Some(guard)
+        }
+        None => {
+            let guard = condvar.wait(guard)
+                .ok().expect("something went wrong");
+
+            Some(guard)
+        }
+    }
+}
@@ -7,6 +7,13 @@ pub trait Queue<T: Send> {
     /// queue is empty.
     fn poll(&self) -> Option<T>;
 
+    /// Returns the number of elements currently contained by the queue.
+    fn len(&self) -> usize;
+
+    /// Returns the maximum number of elements the queue can contain at one
+    /// time, or None if the queue is unbounded.
+    fn capacity(&self) -> Option<usize>;
+
     /// Returns true if the underlying data structure does not contain any
     /// elements.
     fn is_empty(&self) -> bool;
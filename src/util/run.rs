@@ -1,4 +1,4 @@
-use util::async::Future;
+use util::async::{Future, Complete};
 
 pub trait Run : Send {
 
@@ -11,13 +11,36 @@ pub trait Run : Send {
     /// Runs the task on the underlying executor.
     fn run<F>(&self, task: F) where F: FnOnce() + Send;
 
+    /// Runs the task on the underlying executor, returning a future that is
+    /// completed with the task's return value. If the task panics, the future
+    /// fails with an `ExecutionError`.
     fn invoke<F, R>(&self, task: F) -> Future<R, ()> where F: FnOnce() -> R + Send, R: Send {
         let (ret, complete) = Future::pair();
 
         self.run(move || {
-            complete.complete(task());
+            let mut guard = FailOnPanic { complete: Some(complete) };
+            let val = task();
+
+            guard.complete.take()
+                .expect("complete already consumed")
+                .complete(val);
         });
 
         ret
     }
 }
+
+// Fails the future if it is dropped before being completed, which only
+// happens when the task panics.
+struct FailOnPanic<R: Send> {
+    complete: Option<Complete<R, ()>>,
+}
+
+#[unsafe_destructor]
+impl<R: Send> Drop for FailOnPanic<R> {
+    fn drop(&mut self) {
+        if let Some(complete) = self.complete.take() {
+            complete.fail(());
+        }
+    }
+}
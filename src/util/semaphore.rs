@@ -0,0 +1,162 @@
+use super::{deadline, wait_until};
+use std::sync::{Arc, Mutex, Condvar};
+use std::time::Duration;
+
+/// A counting semaphore.
+///
+/// The semaphore maintains a number of permits. `acquire` blocks until a
+/// permit is available and takes it, `release` returns a permit and wakes up
+/// a blocked acquirer. The current implementation is based on a mutex and a
+/// condition variable.
+pub struct Semaphore {
+    inner: Arc<Inner>,
+}
+
+impl Semaphore {
+    pub fn new(permits: usize) -> Semaphore {
+        let inner = Inner {
+            permits: Mutex::new(permits),
+            condvar: Condvar::new(),
+        };
+
+        Semaphore { inner: Arc::new(inner) }
+    }
+
+    /// Returns the number of permits currently available.
+    pub fn available_permits(&self) -> usize {
+        *self.inner.permits.lock()
+            .ok().expect("something went wrong")
+    }
+
+    /// Takes a permit, blocking until one is available.
+    pub fn acquire(&self) {
+        self.inner.acquire_for(Duration::max_value());
+    }
+
+    /// Takes a permit, waiting up to `timeout` for one to become available.
+    /// Returns false if no permit was acquired.
+    pub fn acquire_timeout(&self, timeout: Duration) -> bool {
+        self.inner.acquire_for(timeout)
+    }
+
+    /// Takes a permit if one is available without blocking. Returns false if
+    /// no permit was acquired.
+    pub fn try_acquire(&self) -> bool {
+        self.inner.acquire_for(Duration::milliseconds(0))
+    }
+
+    /// Returns a permit to the semaphore, waking up a blocked acquirer if
+    /// there is one.
+    pub fn release(&self) {
+        let mut permits = self.inner.permits.lock()
+            .ok().expect("something went wrong");
+
+        *permits += 1;
+        self.inner.condvar.notify_one();
+    }
+}
+
+impl Clone for Semaphore {
+    fn clone(&self) -> Semaphore {
+        Semaphore { inner: self.inner.clone() }
+    }
+}
+
+struct Inner {
+    permits: Mutex<usize>,
+    condvar: Condvar,
+}
+
+impl Inner {
+    fn acquire_for(&self, dur: Duration) -> bool {
+        let deadline = deadline(dur);
+
+        let mut permits = self.permits.lock()
+            .ok().expect("something went wrong");
+
+        while *permits == 0 {
+            permits = match wait_until(&self.condvar, permits, deadline) {
+                Some(permits) => permits,
+                None => return false,
+            };
+        }
+
+        *permits -= 1;
+        true
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Semaphore;
+    use std::old_io::timer::sleep;
+    use std::time::Duration;
+    use std::thread::Thread;
+    use std::sync::mpsc::channel;
+
+    #[test]
+    pub fn test_try_acquire() {
+        let sem = Semaphore::new(2);
+
+        assert!(sem.try_acquire());
+        assert!(sem.try_acquire());
+        assert!(!sem.try_acquire());
+        assert_eq!(0, sem.available_permits());
+
+        sem.release();
+        assert!(sem.try_acquire());
+    }
+
+    #[test]
+    pub fn test_acquire_blocks_until_release() {
+        let sem = Semaphore::new(2);
+        let (tx, rx) = channel();
+
+        sem.acquire();
+        sem.acquire();
+
+        {
+            let sem = sem.clone();
+
+            Thread::spawn(move || {
+                sem.acquire();
+                tx.send(()).unwrap();
+            });
+        }
+
+        sleep(millis(50));
+        assert!(rx.try_recv().is_err());
+
+        sem.release();
+        rx.recv().unwrap();
+
+        assert_eq!(0, sem.available_permits());
+    }
+
+    #[test]
+    pub fn test_acquire_timeout_elapses() {
+        let sem = Semaphore::new(0);
+
+        assert!(!sem.acquire_timeout(millis(20)));
+    }
+
+    #[test]
+    pub fn test_acquire_timeout_unblocked_by_release() {
+        let sem = Semaphore::new(0);
+
+        {
+            let sem = sem.clone();
+
+            Thread::spawn(move || {
+                sleep(millis(20));
+                sem.release();
+            });
+        }
+
+        assert!(sem.acquire_timeout(millis(1_000)));
+    }
+
+    fn millis(num: uint) -> Duration {
+        Duration::milliseconds(num as i64)
+    }
+}
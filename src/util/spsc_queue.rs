@@ -0,0 +1,263 @@
+use std::{mem, ptr};
+use std::cell::UnsafeCell;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicPtr, AtomicBool, AtomicUsize, Ordering};
+use std::thread::Thread;
+
+/// Returns the two halves of an unbounded queue optimized for a single
+/// producer and a single consumer.
+///
+/// # Contract
+///
+/// The `Producer` is the only handle that inserts into the queue and the
+/// `Consumer` is the only handle that removes from it. Neither half can be
+/// cloned and both require `&mut self` to operate on the queue, so each side
+/// is used by at most one thread at a time. In exchange, the queue does not
+/// take any locks on the fast path and avoids the CAS contention of a general
+/// purpose design.
+///
+/// A consumer blocked in `take` parks its thread until the producer pushes a
+/// value.
+pub fn spsc_queue<T: Send>() -> (Producer<T>, Consumer<T>) {
+    let inner = Arc::new(QueueInner::new());
+    (Producer { inner: inner.clone() }, Consumer { inner: inner })
+}
+
+/// The inserting half of a queue created with `spsc_queue`.
+pub struct Producer<T> {
+    inner: Arc<QueueInner<T>>,
+}
+
+/// The removing half of a queue created with `spsc_queue`.
+pub struct Consumer<T> {
+    inner: Arc<QueueInner<T>>,
+}
+
+impl<T: Send> Producer<T> {
+    pub fn len(&self) -> usize {
+        self.inner.len.load(Ordering::Relaxed)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Pushes the value onto the queue. The queue is unbounded, so this never
+    /// blocks.
+    pub fn put(&mut self, e: T) {
+        unsafe { self.inner.push(e) }
+    }
+}
+
+impl<T: Send> Consumer<T> {
+    pub fn len(&self) -> usize {
+        self.inner.len.load(Ordering::Relaxed)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn poll(&mut self) -> Option<T> {
+        unsafe { self.inner.pop() }
+    }
+
+    /// Takes from the queue, blocking until there is an element available.
+    pub fn take(&mut self) -> T {
+        unsafe { self.inner.take() }
+    }
+}
+
+// The queue is a singly linked list starting at a sentinel node. The consumer
+// owns `head` and the producer owns `tail`, so neither pointer needs to be
+// atomic. The only point of contact is the `next` link of the tail node,
+// which the producer writes and the consumer reads. `push`, `pop` and `take`
+// are unsafe since they rely on this ownership, which the `Producer` and
+// `Consumer` halves enforce.
+//
+// When the queue is empty, the consumer sets `parked` before checking the
+// queue one last time and parking. The producer clears `parked` after linking
+// a new node and, if it was set, unparks the consumer. Both sides use SeqCst
+// so that either the consumer sees the new node or the producer sees the flag.
+struct QueueInner<T> {
+    // The sentinel node, its `next` is the head of the queue. Consumer only.
+    head: UnsafeCell<*mut Node<T>>,
+
+    // The most recently pushed node. Producer only.
+    tail: UnsafeCell<*mut Node<T>>,
+
+    // Current number of elements
+    len: AtomicUsize,
+
+    // True when the consumer is parked, or about to park
+    parked: AtomicBool,
+
+    // The thread to unpark when a value is pushed
+    consumer: Mutex<Option<Thread>>,
+}
+
+unsafe impl<T: Send> Send for QueueInner<T> {}
+unsafe impl<T: Send> Sync for QueueInner<T> {}
+
+impl<T: Send> QueueInner<T> {
+    fn new() -> QueueInner<T> {
+        let sentinel = Node::new(None);
+
+        QueueInner {
+            head: UnsafeCell::new(sentinel),
+            tail: UnsafeCell::new(sentinel),
+            len: AtomicUsize::new(0),
+            parked: AtomicBool::new(false),
+            consumer: Mutex::new(None),
+        }
+    }
+
+    unsafe fn push(&self, e: T) {
+        let node = Node::new(Some(e));
+
+        // Increment before linking so that the count never underflows when
+        // the consumer pops the node right away
+        self.len.fetch_add(1, Ordering::Relaxed);
+
+        let tail = *self.tail.get();
+        (*tail).next.store(node, Ordering::SeqCst);
+        *self.tail.get() = node;
+
+        if self.parked.swap(false, Ordering::SeqCst) {
+            self.unpark_consumer();
+        }
+    }
+
+    unsafe fn pop(&self) -> Option<T> {
+        let head = *self.head.get();
+        let next = (*head).next.load(Ordering::SeqCst);
+
+        if next.is_null() {
+            return None;
+        }
+
+        // The popped node becomes the new sentinel. The old sentinel can not
+        // be the producer's tail since it has a successor.
+        *self.head.get() = next;
+        let val = (*next).val.take();
+
+        Node::free(head);
+        self.len.fetch_sub(1, Ordering::Relaxed);
+
+        val
+    }
+
+    unsafe fn take(&self) -> T {
+        loop {
+            if let Some(val) = self.pop() {
+                return val;
+            }
+
+            *self.consumer.lock().ok().expect("something went wrong") = Some(Thread::current());
+            self.parked.store(true, Ordering::SeqCst);
+
+            // The producer may have pushed a value before seeing the flag
+            if let Some(val) = self.pop() {
+                self.parked.store(false, Ordering::SeqCst);
+                return val;
+            }
+
+            Thread::park();
+        }
+    }
+
+    fn unpark_consumer(&self) {
+        let consumer = self.consumer.lock()
+            .ok().expect("something went wrong");
+
+        if let Some(ref th) = *consumer {
+            th.unpark();
+        }
+    }
+}
+
+#[unsafe_destructor]
+impl<T: Send> Drop for QueueInner<T> {
+    fn drop(&mut self) {
+        // Both halves have been dropped, so nothing else accesses the queue
+        unsafe {
+            while let Some(_) = self.pop() {
+            }
+
+            Node::free(*self.head.get());
+        }
+    }
+}
+
+struct Node<T> {
+    next: AtomicPtr<Node<T>>,
+    val: Option<T>,
+}
+
+impl<T: Send> Node<T> {
+    fn new(val: Option<T>) -> *mut Node<T> {
+        let node = Box::new(Node {
+            next: AtomicPtr::new(ptr::null_mut()),
+            val: val,
+        });
+
+        unsafe { mem::transmute(node) }
+    }
+
+    unsafe fn free(ptr: *mut Node<T>) {
+        let _: Box<Node<T>> = mem::transmute(ptr);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::spsc_queue;
+    use std::thread::Thread;
+
+    #[test]
+    pub fn test_single_threaded_put_take() {
+        let (mut p, mut c) = spsc_queue();
+
+        assert!(c.is_empty());
+        assert!(c.poll().is_none());
+
+        p.put(1u);
+        p.put(2u);
+        assert_eq!(2, p.len());
+        assert_eq!(2, c.len());
+
+        assert_eq!(1, c.take());
+        assert_eq!(Some(2), c.poll());
+        assert!(c.poll().is_none());
+        assert!(c.is_empty());
+    }
+
+    #[test]
+    pub fn test_single_consumer_single_producer() {
+        let (mut p, mut c) = spsc_queue();
+
+        Thread::spawn(move || {
+            for i in range(0, 100_000u) {
+                p.put(i);
+            }
+        });
+
+        for i in range(0, 100_000u) {
+            assert_eq!(i, c.take());
+        }
+
+        assert!(c.poll().is_none());
+    }
+
+    #[test]
+    pub fn test_drop_with_pending_values() {
+        let (mut p, c) = spsc_queue();
+
+        for i in range(0, 10u) {
+            p.put(vec![i]);
+        }
+
+        drop(p);
+        drop(c);
+    }
+}
@@ -1,9 +1,10 @@
-use super::LinkedQueue;
+use super::{LinkedQueue, Deque, Stealer, Steal};
 use super::queue::SyncQueue;
 use super::run::Run;
+use std::cell::RefCell;
 use std::num::FromPrimitive;
 use std::sync::{Arc, Mutex, Condvar};
-use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::atomic::{self, AtomicUsize, Ordering};
 use std::thread::Thread;
 
 use self::Lifecycle::*;
@@ -67,6 +68,12 @@ impl Run for ThreadPool {
 // It's important that a worker increments the count before pulling from the
 // queue and never touches the queue after decrementing the worker count.
 //
+// Each worker owns a deque. Tasks submitted from a worker thread are pushed
+// onto that worker's deque, and tasks submitted from any other thread go
+// through the shared work queue, which acts as the injector. A worker runs
+// the tasks from its own deque first, then steals from the other workers'
+// deques, and only blocks on the work queue once there is nothing to steal.
+//
 // ## TODO
 //
 // - Poison the thread pool if something goes critically wrong
@@ -142,6 +149,16 @@ impl ThreadPoolInner {
         }
 
         if state.is_running() {
+            // When called from one of the pool's workers, push the task onto
+            // the worker's deque instead of contending on the work queue.
+            match self.push_local(task) {
+                Ok(_) => {
+                    debug!("task pushed onto worker deque");
+                    return;
+                }
+                Err(t) => task = t,
+            }
+
             // The current state is running, attempt to place the task on the
             // queue. If this fails, the queue is full (or is in some other
             // error condition). Return the task to the caller.
@@ -171,6 +188,42 @@ impl ThreadPoolInner {
         debug!("threadpool is not accepting new tasks");
     }
 
+    // Pushes the task onto the deque of the current thread's worker. Returns
+    // the task back if the current thread is not a worker of this pool.
+    fn push_local(&self, task: Box<Task>) -> Result<(), Box<Task>> {
+        let pool = self.core.id();
+
+        let res = LOCAL.with(move |local| {
+            let mut local = local.borrow_mut();
+
+            if let Some(ref mut local) = *local {
+                if local.pool == pool {
+                    local.deque.push(task);
+                    return Ok(());
+                }
+            }
+
+            Err(task)
+        });
+
+        if res.is_ok() {
+            // Pairs with the idle count increment in `Worker::get_task`.
+            // Either the idle worker sees the task when it looks for one to
+            // steal, or the push sees the idle worker and wakes it up.
+            atomic::fence(Ordering::SeqCst);
+
+            if self.core.idle.load(Ordering::SeqCst) > 0 {
+                // The no-op wakes up a worker blocked on the work queue,
+                // which then steals the task. If the queue is full, workers
+                // are not blocked on it anyway.
+                let wakeup: Box<Task> = Box::new(|| {});
+                let _ = self.work_queue.offer(Some(wakeup));
+            }
+        }
+
+        res
+    }
+
     fn shutdown(&self, target: Lifecycle) {
         // Transition from RUNNING -> SHUTDOWN
         let mut state = self.core.state.load(Ordering::Relaxed);
@@ -310,6 +363,9 @@ struct Worker {
     // Core shared by ThreadPool and Worker
     core: Arc<Core>,
 
+    // Identifies the worker's deque in the core
+    id: usize,
+
     // The task to run when the thread first starts
     initial_task: Option<Box<Task>>,
 
@@ -318,20 +374,29 @@ struct Worker {
 
     // Checked in the drop function whether or not the thread panicked
     panicked: bool,
+
+    // Set once the worker received a no-op token. The worker no longer pulls
+    // from the queue, but still steals the tasks left in other deques.
+    draining: bool,
 }
 
 impl Worker {
     fn new(core: Arc<Core>, initial_task: Option<Box<Task>>, queue: Box<WorkQueue>) -> Worker {
+        let id = core.next_worker_id.fetch_add(1, Ordering::Relaxed);
+
         Worker {
             core: core,
+            id: id,
             initial_task: initial_task,
             work_queue: queue,
             panicked: false,
+            draining: false,
         }
     }
 
     fn run(&mut self) {
         self.panicked = true;
+        self.register();
 
         while let Some(task) = self.get_task() {
             task.invoke();
@@ -340,6 +405,32 @@ impl Worker {
         self.panicked = false;
     }
 
+    // Creates the worker's deque and makes it available to the other workers
+    // for stealing.
+    fn register(&self) {
+        let deque = Deque::new();
+        let pool = self.core.id();
+
+        self.core.register(self.id, deque.stealer());
+
+        LOCAL.with(move |local| {
+            *local.borrow_mut() = Some(Local { pool: pool, deque: deque });
+        });
+    }
+
+    // Removes the worker's deque. If tasks are left in it, for example
+    // because a task panicked, they remain available to the other workers.
+    fn deregister(&self) {
+        let local = LOCAL.with(|local| local.borrow_mut().take());
+
+        let orphaned = match local {
+            Some(local) => !local.deque.is_empty(),
+            None => false,
+        };
+
+        self.core.deregister(self.id, orphaned);
+    }
+
     // Gets the next task, blocking if necessary. Returns None if the worker
     // should shutdown
     fn get_task(&mut self) -> Option<Box<Task>> {
@@ -364,6 +455,29 @@ impl Worker {
                 break;
             }
 
+            // Run the tasks submitted from this worker first, most recently
+            // pushed first
+            if let Some(t) = pop_local() {
+                task = Some(t);
+                continue;
+            }
+
+            if self.draining {
+                // The pool is shutting down, help with the tasks left in the
+                // other deques before exiting
+                match self.core.steal() {
+                    Some(t) => {
+                        task = Some(t);
+                        continue;
+                    }
+                    None => {
+                        debug!("no tasks left to steal -- shutting down");
+                        self.decrement_worker_count(false);
+                        return None;
+                    }
+                }
+            }
+
             let wc = state.worker_count();
 
             if wc > self.core.maximum_pool_size {
@@ -384,7 +498,22 @@ impl Worker {
                 continue;
             }
 
-            match self.work_queue.take() {
+            // Look for a task in the other workers' deques before blocking on
+            // the queue. The worker counts as idle from now on, so that a
+            // worker pushing onto its deque knows to wake it up.
+            self.core.idle.fetch_add(1, Ordering::SeqCst);
+
+            if let Some(t) = self.core.steal() {
+                self.core.idle.fetch_sub(1, Ordering::SeqCst);
+                task = Some(t);
+                continue;
+            }
+
+            let next = self.work_queue.take();
+
+            self.core.idle.fetch_sub(1, Ordering::SeqCst);
+
+            match next {
                 Some(t) => {
                     // Grab the task, but the loop will restart in order to
                     // check the state again. If the state transitioned to STOP
@@ -393,13 +522,10 @@ impl Worker {
                     task = Some(t);
                 }
                 None => {
-                    debug!("received no-op token -- shutting down");
-                    // No more tasks should be removed from the queue, exit the
-                    // worker
-                    self.decrement_worker_count(false);
-
-                    // Nothing else to do
-                    return None;
+                    debug!("received no-op token -- draining deques");
+                    // No more tasks should be removed from the queue. The
+                    // worker exits once the other deques are empty.
+                    self.draining = true;
                 }
             }
         }
@@ -428,12 +554,56 @@ impl Worker {
 
 impl Drop for Worker {
     fn drop(&mut self) {
+        self.deregister();
+
         if self.panicked {
-            self.decrement_worker_count(true);
+            let state = self.core.state.load(Ordering::Relaxed);
+
+            if state.lifecycle() >= STOP {
+                self.decrement_worker_count(true);
+                return;
+            }
+
+            warn!("task panicked -- replacing worker");
+
+            // Spawn a replacement worker so that tasks already in the queue
+            // are still processed. The replacement takes over the slot of the
+            // panicked worker, so the worker count is left untouched. If the
+            // pool stops before the replacement starts, it will decrement the
+            // count itself.
+            let mut worker = Worker::new(
+                self.core.clone(), None,
+                self.work_queue.boxed_clone());
+
+            // A worker that already consumed its no-op token passes the
+            // draining on, otherwise the replacement would wait on the queue
+            // forever.
+            worker.draining = self.draining;
+
+            Thread::spawn(move || worker.run());
         }
     }
 }
 
+thread_local!(static LOCAL: RefCell<Option<Local>> = RefCell::new(None));
+
+// The deque of the worker running on the current thread
+struct Local {
+    // Identifies the pool that the worker belongs to
+    pool: usize,
+    deque: Deque<Box<Task>>,
+}
+
+// Pops a task from the deque of the worker running on the current thread
+fn pop_local() -> Option<Box<Task>> {
+    LOCAL.with(|local| {
+        match *local.borrow_mut() {
+            Some(ref mut local) => local.deque.pop(),
+            None => None,
+        }
+    })
+}
+
 trait WorkQueue : SyncQueue<Option<Box<Task>>> + Send + Sync {
     fn boxed_clone(&self) -> Box<WorkQueue>;
 }
@@ -515,6 +685,18 @@ struct Core {
     // Maximum pool size. Note that the actual maximum is internally
     // bounded by CAPACITY.
     maximum_pool_size: u32,
+
+    // The stealing end of each worker's deque. Thieves hold the lock while
+    // stealing so that entries can be removed safely; owners never take it
+    // to push or pop.
+    stealers: Mutex<Vec<Registration>>,
+
+    // Used to assign each worker an id
+    next_worker_id: AtomicUsize,
+
+    // The number of workers looking for a task to steal or blocked on the
+    // work queue
+    idle: AtomicUsize,
 }
 
 impl Core {
@@ -525,9 +707,74 @@ impl Core {
             termination: Condvar::new(),
             core_pool_size: core_pool_size,
             maximum_pool_size: maximum_pool_size,
+            stealers: Mutex::new(vec![]),
+            next_worker_id: AtomicUsize::new(0),
+            idle: AtomicUsize::new(0),
+        }
+    }
+
+    // Identifies the pool, so that workers do not push tasks submitted to
+    // another pool onto their own deque.
+    fn id(&self) -> usize {
+        self as *const Core as usize
+    }
+
+    fn register(&self, worker: usize, stealer: Stealer<Box<Task>>) {
+        let mut stealers = self.stealers.lock()
+            .ok().expect("something went wrong");
+
+        stealers.push(Registration {
+            worker: worker,
+            stealer: stealer,
+            orphaned: false,
+        });
+    }
+
+    fn deregister(&self, worker: usize, orphaned: bool) {
+        let mut guard = self.stealers.lock()
+            .ok().expect("something went wrong");
+
+        let stealers = &mut *guard;
+
+        if let Some(i) = stealers.iter().position(|r| r.worker == worker) {
+            if orphaned {
+                stealers[i].orphaned = true;
+            } else {
+                stealers.remove(i);
+            }
         }
     }
 
+    // Steals a task from any of the workers' deques. Returns None once all
+    // of them have been seen empty.
+    fn steal(&self) -> Option<Box<Task>> {
+        let mut guard = self.stealers.lock()
+            .ok().expect("something went wrong");
+
+        let stealers = &mut *guard;
+        let mut i = 0;
+
+        while i < stealers.len() {
+            let res = stealers[i].stealer.steal();
+
+            match res {
+                Steal::Data(task) => return Some(task),
+                // Lost a race with the owner, try the same deque again
+                Steal::Abort => {}
+                Steal::Empty => {
+                    if stealers[i].orphaned {
+                        // The worker is gone and its last task was taken
+                        stealers.remove(i);
+                    } else {
+                        i += 1;
+                    }
+                }
+            }
+        }
+
+        None
+    }
+
     fn await_termination(&self) {
         let mut lock = self.mutex.lock()
             .ok().expect("something went wrong");
@@ -554,6 +801,15 @@ impl Core {
     }
 }
 
+// A worker's deque, as seen by the other workers
+struct Registration {
+    worker: usize,
+    stealer: Stealer<Box<Task>>,
+
+    // Set once the worker has exited with tasks left in its deque
+    orphaned: bool,
+}
+
 struct AtomicState {
     atomic: AtomicUsize,
 }
@@ -683,6 +939,10 @@ impl<F: FnOnce() + Send> Task for F {
 #[cfg(test)]
 mod test {
     use super::ThreadPool;
+    use util::Run;
+    use util::async::AsyncError::ExecutionError;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
     use std::sync::mpsc::*;
 
     #[test]
@@ -747,6 +1007,127 @@ mod test {
         }
     }
 
+    #[test]
+    pub fn test_shutdown_drains_pending_tasks() {
+        let tp = ThreadPool::fixed_size(4);
+        let count = Arc::new(AtomicUsize::new(0));
+
+        for _ in range(0, 1000u) {
+            let count = count.clone();
+
+            tp.run(move || {
+                count.fetch_add(1, Ordering::Relaxed);
+            });
+        }
+
+        tp.shutdown();
+        tp.await_termination();
+
+        assert_eq!(1000, count.load(Ordering::Relaxed));
+    }
+
+    #[test]
+    pub fn test_panicking_task_does_not_kill_pool() {
+        let tp = ThreadPool::single_thread();
+        let (tx1, rx1) = channel::<()>();
+        let (tx2, rx2) = channel();
+
+        tp.run(move || {
+            // Wait until the next task is queued
+            rx1.recv().unwrap();
+            panic!("boom");
+        });
+
+        tp.run(move || {
+            tx2.send("hi").unwrap();
+        });
+
+        tx1.send(()).unwrap();
+
+        assert_eq!("hi", rx2.recv().unwrap());
+    }
+
+    #[test]
+    pub fn test_idle_worker_steals_task_submitted_by_worker() {
+        let tp = Arc::new(ThreadPool::fixed_size(2));
+        let (tx, rx) = channel();
+
+        // Start both workers
+        for _ in range(0, 2u) {
+            let tx = tx.clone();
+            tp.run(move || tx.send("started").unwrap());
+        }
+
+        assert_eq!("started", rx.recv().unwrap());
+        assert_eq!("started", rx.recv().unwrap());
+
+        let pool = tp.clone();
+
+        tp.run(move || {
+            let (sub_tx, sub_rx) = channel();
+
+            // Pushed onto this worker's deque
+            pool.run(move || sub_tx.send("stolen").unwrap());
+
+            // Keep this worker busy, the other one has to steal the task
+            tx.send(sub_rx.recv().unwrap()).unwrap();
+        });
+
+        assert_eq!("stolen", rx.recv().unwrap());
+    }
+
+    #[test]
+    pub fn test_shutdown_drains_worker_deques() {
+        let tp = Arc::new(ThreadPool::fixed_size(2));
+        let count = Arc::new(AtomicUsize::new(0));
+        let (tx, rx) = channel();
+
+        let pool = tp.clone();
+        let c = count.clone();
+
+        tp.run(move || {
+            for _ in range(0, 100u) {
+                let c = c.clone();
+
+                pool.run(move || {
+                    c.fetch_add(1, Ordering::Relaxed);
+                });
+            }
+
+            tx.send(()).unwrap();
+        });
+
+        // Wait until all tasks have been submitted
+        rx.recv().unwrap();
+
+        tp.shutdown();
+        tp.await_termination();
+
+        assert_eq!(100, count.load(Ordering::Relaxed));
+    }
+
+    #[test]
+    pub fn test_invoke_completes_future() {
+        let tp = ThreadPool::fixed_size(1);
+        let res = tp.invoke(|| 1u + 1).await();
+
+        assert_eq!(2, res.unwrap());
+    }
+
+    #[test]
+    pub fn test_invoke_panic_fails_future() {
+        let tp = ThreadPool::fixed_size(1);
+        let res = tp.invoke(|| -> uint { panic!("boom") }).await();
+
+        match res {
+            Err(ExecutionError(())) => {}
+            _ => panic!("expected the future to fail"),
+        }
+
+        // The pool is still usable after a task panicked
+        assert_eq!(3, tp.invoke(|| 3u).await().unwrap());
+    }
+
     fn sleep(ms: uint) {
         use std::old_io::timer::sleep;
         use std::time::Duration;
@@ -1,6 +1,7 @@
 #![feature(int_uint)]
 #![feature(core, io, std_misc)]
 
+#[macro_use]
 extern crate syncbox;
 
 #[macro_use]
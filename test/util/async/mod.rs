@@ -15,7 +15,11 @@
 //!
 //! # Stream
 
+use syncbox::util::Run;
 use syncbox::util::async::*;
+use std::mem;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
 /*
  * Last ported test: test_producer_fail_before_consumer_take
@@ -23,25 +27,63 @@ use syncbox::util::async::*;
 
 // == Future tests ==
 mod test_future_and;
+mod test_future_and_then_spawn;
 mod test_future_await;
 mod test_future_cancel;
+mod test_future_complete_with;
+mod test_future_delay;
+mod test_future_error;
+mod test_future_from_poll;
+mod test_future_from_queue;
+mod test_future_inspect;
+mod test_future_into_sender;
+mod test_future_map_both;
+mod test_future_map_err;
+mod test_future_map_result;
+mod test_future_of;
+mod test_future_on_executor;
 mod test_future_or;
+mod test_future_poll;
 mod test_future_receive;
+mod test_future_timeout;
+mod test_future_unwrap_or;
+mod test_future_with_token;
 
 // == Join tests ==
+mod test_all;
 mod test_join;
 
+// == Retry tests ==
+mod test_retry;
+
 // == Select tests ==
 mod test_select;
 
 // == Stream tests ==
 mod test_stream_await;
 mod test_stream_cancel;
+mod test_stream_buffered;
+mod test_stream_chain;
+mod test_stream_collect;
+mod test_stream_debounce;
+mod test_stream_each;
+mod test_stream_filter;
+mod test_stream_flat_map;
+mod test_stream_from_iter;
+mod test_stream_from_queue;
+mod test_stream_group_by;
 mod test_stream_iter;
 mod test_stream_map;
+mod test_stream_merge;
+mod test_stream_peek;
+mod test_stream_poll;
 mod test_stream_receive;
 mod test_stream_reduce;
+mod test_stream_scan;
+mod test_stream_skip;
+mod test_stream_split;
 mod test_stream_take;
+mod test_stream_zip;
 
 /*
  *
@@ -68,6 +110,89 @@ fn spawn<F: FnOnce() + Send>(f: F) {
 
 fn sleep(ms: uint) {
     use std::old_io::timer::sleep;
-    use std::time::Duration;
-    sleep(Duration::milliseconds(ms as i64));
+    sleep(millis(ms));
+}
+
+fn millis(ms: uint) -> Duration {
+    Duration::milliseconds(ms as i64)
+}
+
+// Returns a stream yielding the values from another thread, each one after
+// sleeping for the given number of milliseconds.
+fn produce(vals: Vec<(uint, uint)>) -> Stream<uint, ()> {
+    let (stream, generate) = Stream::pair();
+
+    spawn(move || {
+        let mut generate = generate;
+
+        for (v, ms) in vals.into_iter() {
+            generate = generate.await().unwrap();
+            sleep(ms);
+            generate.send(v);
+        }
+
+        generate.await().unwrap().done();
+    });
+
+    stream
+}
+
+/// An executor that queues tasks until they are explicitly run on the test
+/// thread. This allows tests to control when callbacks fire without relying
+/// on sleeps.
+///
+/// There is no virtual clock: `Future::delay`, `timeout` and `from_poll` are
+/// driven by the shared timer in real time, so tests exercising them still
+/// have to wait.
+#[derive(Clone)]
+struct ManualRun {
+    tasks: Arc<Mutex<Vec<Box<Task>>>>,
+}
+
+impl ManualRun {
+    fn new() -> ManualRun {
+        ManualRun { tasks: Arc::new(Mutex::new(vec![])) }
+    }
+
+    fn pending(&self) -> uint {
+        self.tasks.lock().unwrap().len()
+    }
+
+    /// Runs queued tasks in FIFO order, including tasks queued by the tasks
+    /// being run, until the queue is empty. Returns the number of tasks run.
+    fn run_pending(&self) -> uint {
+        let mut ran = 0;
+
+        loop {
+            let tasks: Vec<Box<Task>> = {
+                let mut tasks = self.tasks.lock().unwrap();
+                mem::replace(&mut *tasks, vec![])
+            };
+
+            if tasks.is_empty() {
+                return ran;
+            }
+
+            for task in tasks.into_iter() {
+                task.invoke();
+                ran += 1;
+            }
+        }
+    }
+}
+
+impl Run for ManualRun {
+    fn run<F>(&self, task: F) where F: FnOnce() + Send {
+        self.tasks.lock().unwrap().push(Box::new(task));
+    }
+}
+
+trait Task : Send {
+    fn invoke(self: Box<Self>);
+}
+
+impl<F: FnOnce() + Send> Task for F {
+    fn invoke(self: Box<F>) {
+        (*self)();
+    }
 }
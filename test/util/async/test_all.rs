@@ -0,0 +1,49 @@
+use syncbox::util::async::{self, Async, Future};
+use std::sync::mpsc::channel;
+
+#[test]
+pub fn test_all_with_failure() {
+    let (f1, c1) = Future::<i32, &'static str>::pair();
+    let (f2, c2) = Future::<i32, &'static str>::pair();
+    let (f3, c3) = Future::<i32, &'static str>::pair();
+    let (tx, rx) = channel();
+
+    async::all(vec![f1, f2, f3]).receive(move |res| {
+        tx.send(res.unwrap()).unwrap();
+    });
+
+    c2.fail("nope");
+    assert!(rx.try_recv().is_err());
+
+    c3.complete(3);
+    assert!(rx.try_recv().is_err());
+
+    c1.complete(1);
+
+    let mut res = rx.recv().unwrap().into_iter();
+
+    assert_eq!(1, res.next().unwrap().unwrap());
+    assert_eq!("nope", res.next().unwrap().unwrap_err().unwrap());
+    assert_eq!(3, res.next().unwrap().unwrap());
+    assert!(res.next().is_none());
+}
+
+#[test]
+pub fn test_all_with_cancellation() {
+    let (f1, c1) = Future::<i32, ()>::pair();
+    let (f2, c2) = Future::<i32, ()>::pair();
+
+    drop(c1);
+    c2.complete(2);
+
+    let res = async::all(vec![f1, f2]).await().unwrap();
+
+    assert!(res[0].as_ref().unwrap_err().is_cancellation());
+    assert_eq!(2, *res[1].as_ref().unwrap());
+}
+
+#[test]
+pub fn test_all_empty_vec() {
+    let futures: Vec<Future<i32, ()>> = vec![];
+    assert!(async::all(futures).await().unwrap().is_empty());
+}
@@ -29,3 +29,26 @@ pub fn test_and_success_async() {
     assert_eq!("first", rx.recv().unwrap());
     assert_eq!("second", rx.recv().unwrap());
 }
+
+#[test]
+pub fn test_and_error_async() {
+    let (f1, c1) = Future::<(), &'static str>::pair();
+    let (f2, c2) = Future::<i32, &'static str>::pair();
+    let (tx1, rx) = channel();
+    let tx2 = tx1.clone();
+
+    c2.receive(move |res| {
+        // The second future is never driven, only canceled
+        assert!(res.unwrap_err().is_cancellation());
+        tx2.send("canceled").unwrap();
+    });
+
+    f1.and(f2).receive(move |res| {
+        tx1.send(res.unwrap_err().unwrap()).unwrap();
+    });
+
+    c1.fail("nope");
+
+    assert_eq!("nope", rx.recv().unwrap());
+    assert_eq!("canceled", rx.recv().unwrap());
+}
@@ -0,0 +1,37 @@
+use syncbox::util::ThreadPool;
+use syncbox::util::async::*;
+use std::sync::mpsc::channel;
+use std::thread::{self, Thread};
+
+#[test]
+pub fn test_and_then_spawn_runs_on_executor() {
+    let (f, c) = Future::<uint, ()>::pair();
+    let (tx, rx) = channel();
+
+    let f = f.and_then_spawn(ThreadPool::single_thread(), move |v| {
+        tx.send(Thread::current().name().map(|s| s.to_string())).unwrap();
+        Ok(v + 1)
+    });
+
+    // Complete the future from a named thread
+    thread::Builder::new().name("completer".to_string()).spawn(move || {
+        c.complete(123);
+    });
+
+    assert_eq!(124, f.await().unwrap());
+
+    let name = rx.recv().unwrap();
+    assert!(name != Some("completer".to_string()));
+}
+
+#[test]
+pub fn test_and_then_spawn_propagates_error() {
+    let (f, c) = Future::<uint, &'static str>::pair();
+
+    let f = f.and_then_spawn(ThreadPool::single_thread(), move |_| -> AsyncResult<uint, &'static str> {
+        panic!("nope")
+    });
+
+    c.fail("failed");
+    assert_eq!("failed", f.await().unwrap_err().unwrap());
+}
@@ -1,5 +1,5 @@
 use syncbox::util::async::*;
-use super::{spawn, sleep};
+use super::{spawn, sleep, millis};
 use std::sync::mpsc::channel;
 use std::sync::Arc;
 use std::sync::atomic::AtomicBool;
@@ -140,3 +140,38 @@ pub fn test_producer_drops_after_consumer_await() {
     let err = f.await().unwrap_err();
     assert!(err.is_cancellation());
 }
+
+#[test]
+pub fn test_complete_before_await_timeout() {
+    let (f, c) = Future::<&'static str, ()>::pair();
+
+    c.complete("zomg");
+    assert_eq!(f.await_timeout(millis(50)).unwrap(), "zomg");
+}
+
+#[test]
+pub fn test_complete_during_await_timeout() {
+    let (f, c) = Future::<&'static str, ()>::pair();
+
+    spawn(move || {
+        sleep(50);
+        c.complete("zomg");
+    });
+
+    assert_eq!(f.await_timeout(millis(500)).unwrap(), "zomg");
+}
+
+#[test]
+pub fn test_await_timeout_elapses() {
+    let (f, c) = Future::<&'static str, ()>::pair();
+
+    let err = f.await_timeout(millis(50)).unwrap_err();
+
+    match err {
+        AsyncError::TimeoutError => {}
+        _ => panic!("unexpected error {:?}", err),
+    }
+
+    // The future has been canceled
+    assert!(c.await().unwrap_err().is_cancellation());
+}
@@ -1,4 +1,6 @@
 use syncbox::util::async::*;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::mpsc::channel;
 
 #[test]
@@ -26,5 +28,74 @@ pub fn test_future_cancel_after_complete() {
     assert_eq!(123, rx.recv().unwrap());
 }
 
+#[test]
+pub fn test_future_cancel_before_producer_complete() {
+    let (future, complete) = Future::<i32, ()>::pair();
+    let (tx, rx) = channel();
+
+    complete.receive(move |res| {
+        assert!(res.unwrap_err().is_cancellation());
+        tx.send("canceled").unwrap();
+    });
+
+    future.cancel();
+    assert_eq!("canceled", rx.recv().unwrap());
+}
+
+#[test]
+pub fn test_future_cancel_after_producer_complete() {
+    let drops = Arc::new(AtomicUsize::new(0));
+    let (future, complete) = Future::<DropCount, ()>::pair();
+
+    complete.complete(DropCount(drops.clone()));
+    assert_eq!(0, drops.load(Ordering::SeqCst));
+
+    // The value has already been provided, canceling simply drops it
+    future.cancel();
+    assert_eq!(1, drops.load(Ordering::SeqCst));
+}
+
 // TODO:
 // - Test blocking & cancel
+
+#[test]
+pub fn test_future_cancel_receive_before_complete() {
+    let (future, complete) = Future::<i32, ()>::pair();
+    let (tx, rx) = channel();
+
+    let cancel = future.receive(move |res| {
+        tx.send(res.unwrap()).unwrap();
+    });
+
+    let future = cancel.cancel().expect("cancel failed");
+
+    complete.complete(123);
+
+    // The withdrawn callback never ran
+    assert!(rx.try_recv().is_err());
+    assert_eq!(123, future.expect().unwrap());
+}
+
+#[test]
+pub fn test_future_cancel_receive_after_complete() {
+    let (future, complete) = Future::<i32, ()>::pair();
+    let (tx, rx) = channel();
+
+    let cancel = future.receive(move |res| {
+        tx.send(res.unwrap()).unwrap();
+    });
+
+    complete.complete(123);
+
+    assert!(cancel.cancel().is_none());
+    assert_eq!(123, rx.recv().unwrap());
+}
+
+// Counts how many times it has been dropped
+struct DropCount(Arc<AtomicUsize>);
+
+impl Drop for DropCount {
+    fn drop(&mut self) {
+        self.0.fetch_add(1, Ordering::SeqCst);
+    }
+}
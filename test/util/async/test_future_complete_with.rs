@@ -0,0 +1,54 @@
+use syncbox::util::async::*;
+use std::sync::mpsc::channel;
+
+#[test]
+pub fn test_complete_with_success() {
+    let (f1, c1) = Future::<i32, ()>::pair();
+    let (f2, c2) = Future::<i32, ()>::pair();
+    let (tx, rx) = channel();
+
+    c1.complete_with(f2);
+
+    f1.receive(move |res| tx.send(res.unwrap()).unwrap());
+    assert!(rx.try_recv().is_err());
+
+    // Completing the delegated-to future completes the original consumer
+    c2.complete(123);
+    assert_eq!(123, rx.recv().unwrap());
+}
+
+#[test]
+pub fn test_complete_with_failure() {
+    let (f1, c1) = Future::<i32, &'static str>::pair();
+    let (f2, c2) = Future::<i32, &'static str>::pair();
+
+    c1.complete_with(f2);
+    c2.fail("nope");
+
+    assert_eq!("nope", f1.await().unwrap_err().unwrap());
+}
+
+#[test]
+pub fn test_complete_with_is_lazy() {
+    let (f1, c1) = Future::<i32, ()>::pair();
+    let (f2, c2) = Future::<i32, ()>::pair();
+
+    c1.complete_with(f2);
+
+    // The consumer has not registered interest yet
+    assert!(!c2.is_ready());
+
+    f1.receive(move |_| {});
+    assert!(c2.is_ready());
+}
+
+#[test]
+pub fn test_complete_with_propagates_cancellation() {
+    let (f1, c1) = Future::<i32, ()>::pair();
+    let (f2, c2) = Future::<i32, ()>::pair();
+
+    c1.complete_with(f2);
+    drop(f1);
+
+    assert!(c2.await().is_err());
+}
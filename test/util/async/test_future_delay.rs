@@ -0,0 +1,60 @@
+use syncbox::util::async::*;
+use std::sync::mpsc::channel;
+use super::{millis, sleep};
+
+#[test]
+pub fn test_delay_completes_after_duration() {
+    let (tx, rx) = channel();
+
+    Future::<(), ()>::delay(millis(50)).receive(move |res| {
+        tx.send(res.is_ok()).unwrap();
+    });
+
+    sleep(20);
+    assert!(rx.try_recv().is_err());
+
+    assert!(rx.recv().unwrap());
+}
+
+#[test]
+pub fn test_delays_complete_in_deadline_order() {
+    let (tx, rx) = channel();
+
+    for &ms in [60u, 20, 40].iter() {
+        let tx = tx.clone();
+
+        Future::<(), ()>::delay(millis(ms)).receive(move |res| {
+            res.unwrap();
+            tx.send(ms).unwrap();
+        });
+    }
+
+    let vals: Vec<uint> = range(0, 3).map(|_| rx.recv().unwrap()).collect();
+    assert_eq!([20, 40, 60].as_slice(), vals.as_slice());
+}
+
+#[test]
+pub fn test_dropping_delay_before_interest() {
+    let (tx, rx) = channel();
+
+    let f = Future::<(), ()>::delay(millis(20)).map(move |_| tx.send(()).unwrap());
+    drop(f);
+
+    sleep(50);
+    assert!(rx.try_recv().is_err());
+}
+
+#[test]
+pub fn test_canceling_delay_callback() {
+    let (tx, rx) = channel();
+
+    let cancel = Future::<(), ()>::delay(millis(20)).receive(move |_| {
+        tx.send(()).unwrap();
+    });
+
+    // The future is handed back, then dropped
+    drop(cancel.cancel());
+
+    sleep(50);
+    assert!(rx.try_recv().is_err());
+}
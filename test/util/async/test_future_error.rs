@@ -0,0 +1,65 @@
+use syncbox::util::async::*;
+use std::sync::mpsc::channel;
+
+#[test]
+pub fn test_error_is_ready() {
+    let f = Future::<i32, &'static str>::error("bad");
+
+    assert!(f.is_ready());
+    assert!(f.is_err());
+    assert_eq!("bad", f.poll().unwrap().unwrap_err().unwrap());
+}
+
+#[test]
+pub fn test_error_receive_is_synchronous() {
+    let (tx, rx) = channel();
+
+    Future::<i32, &'static str>::error("bad").receive(move |res| {
+        tx.send(res.unwrap_err().unwrap()).unwrap();
+    });
+
+    // The callback has been invoked before `receive` returned
+    assert_eq!("bad", rx.try_recv().unwrap());
+}
+
+#[test]
+pub fn test_error_map_skips_closure() {
+    let f = Future::<i32, &'static str>::error("bad")
+        .map(|_| -> i32 { panic!("map closure invoked") });
+
+    assert_eq!("bad", f.await().unwrap_err().unwrap());
+}
+
+#[derive(Debug, PartialEq)]
+struct ParseError {
+    line: u32,
+    msg: &'static str,
+}
+
+#[test]
+pub fn test_custom_error_through_combinators() {
+    let (f, c) = Future::<i32, ParseError>::pair();
+
+    let res = f
+        .map(|v| v + 1)
+        .and_then(|v| Ok(v * 2));
+
+    c.fail(ParseError { line: 3, msg: "unexpected token" });
+
+    let err = res.await().unwrap_err().unwrap();
+    assert_eq!(ParseError { line: 3, msg: "unexpected token" }, err);
+}
+
+#[test]
+pub fn test_custom_error_from_and_then() {
+    let res = Future::<i32, ParseError>::of(1)
+        .and_then(|v| {
+            if v > 0 {
+                Err(AsyncError::wrap(ParseError { line: 1, msg: "positive" }))
+            } else {
+                Ok(v)
+            }
+        });
+
+    assert_eq!(1, res.await().unwrap_err().unwrap().line);
+}
@@ -0,0 +1,86 @@
+use syncbox::util::async::*;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use super::{spawn, sleep};
+
+#[test]
+pub fn test_from_poll_completes_when_flag_flips() {
+    let flag = Arc::new(AtomicBool::new(false));
+
+    let f = {
+        let flag = flag.clone();
+
+        Future::<&'static str, ()>::from_poll(move || {
+            if flag.load(Ordering::SeqCst) {
+                Some(Ok("done"))
+            } else {
+                None
+            }
+        })
+    };
+
+    spawn(move || {
+        sleep(50);
+        flag.store(true, Ordering::SeqCst);
+    });
+
+    assert_eq!("done", f.await().unwrap());
+}
+
+#[test]
+pub fn test_from_poll_failure() {
+    let f = Future::<(), &'static str>::from_poll(move || {
+        Some(Err(AsyncError::wrap("nope")))
+    });
+
+    assert_eq!("nope", f.await().unwrap_err().unwrap());
+}
+
+#[test]
+pub fn test_from_poll_is_lazy() {
+    let polls = Arc::new(AtomicUsize::new(0));
+
+    let f = {
+        let polls = polls.clone();
+
+        Future::<(), ()>::from_poll(move || {
+            polls.fetch_add(1, Ordering::SeqCst);
+            Some(Ok(()))
+        })
+    };
+
+    sleep(20);
+    assert_eq!(0, polls.load(Ordering::SeqCst));
+
+    f.await().unwrap();
+    assert_eq!(1, polls.load(Ordering::SeqCst));
+}
+
+#[test]
+pub fn test_from_poll_stops_once_canceled() {
+    let polls = Arc::new(AtomicUsize::new(0));
+
+    let f = {
+        let polls = polls.clone();
+
+        Future::<(), ()>::from_poll(move || {
+            polls.fetch_add(1, Ordering::SeqCst);
+            None
+        })
+    };
+
+    let cancel = f.receive(move |_| panic!("the future should not complete"));
+
+    sleep(20);
+    assert!(polls.load(Ordering::SeqCst) > 0);
+
+    // Dropping the future cancels it
+    drop(cancel.cancel());
+
+    // Give an already scheduled poll the time to run
+    sleep(100);
+    let n = polls.load(Ordering::SeqCst);
+
+    sleep(100);
+    assert_eq!(n, polls.load(Ordering::SeqCst));
+}
@@ -0,0 +1,82 @@
+use syncbox::util::LinkedQueue;
+use syncbox::util::async::*;
+use std::sync::mpsc::channel;
+use super::sleep;
+
+#[test]
+pub fn test_future_from_queue_takes_one_value() {
+    let queue = LinkedQueue::new();
+
+    queue.put(1u);
+    queue.put(2u);
+
+    let f = Future::<uint, ()>::from_queue(queue.clone());
+    assert_eq!(1, f.await().unwrap());
+
+    // Only one value was removed
+    assert_eq!(1, queue.len());
+    assert_eq!(Some(2), queue.poll());
+}
+
+#[test]
+pub fn test_future_from_queue_does_not_block_caller() {
+    let queue = LinkedQueue::new();
+    let (tx, rx) = channel();
+
+    Future::<uint, ()>::from_queue(queue.clone()).receive(move |res| {
+        tx.send(res.unwrap()).unwrap();
+    });
+
+    // The queue is being polled
+    sleep(20);
+    assert!(rx.try_recv().is_err());
+
+    queue.put(123);
+    assert_eq!(123, rx.recv().unwrap());
+    assert!(queue.is_empty());
+}
+
+#[test]
+pub fn test_future_from_queue_is_lazy() {
+    let queue = LinkedQueue::new();
+
+    queue.put(1u);
+
+    let f = Future::<uint, ()>::from_queue(queue.clone());
+
+    sleep(20);
+    assert_eq!(1, queue.len());
+
+    // Dropping the future withdraws the take
+    drop(f);
+
+    sleep(20);
+    assert_eq!(1, queue.len());
+}
+
+#[test]
+pub fn test_future_from_queue_canceled_on_close() {
+    let queue: LinkedQueue<uint> = LinkedQueue::new();
+    let f = Future::<uint, ()>::from_queue(queue.clone());
+
+    queue.close();
+    assert!(f.await().unwrap_err().is_cancellation());
+}
+
+#[test]
+pub fn test_future_from_queue_canceled_leaves_value() {
+    let queue = LinkedQueue::new();
+    let f = Future::<uint, ()>::from_queue(queue.clone());
+
+    let cancel = f.receive(move |_| panic!("the future should not complete"));
+
+    // Dropping the future cancels it
+    sleep(20);
+    drop(cancel.cancel());
+
+    queue.put(123u);
+
+    // Give an already scheduled poll the time to run
+    sleep(100);
+    assert_eq!(Some(123), queue.poll());
+}
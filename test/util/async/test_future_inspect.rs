@@ -0,0 +1,40 @@
+use syncbox::util::async::*;
+use std::sync::mpsc::channel;
+use std::sync::Arc;
+use std::sync::atomic::AtomicUint;
+use std::sync::atomic::Ordering::Relaxed;
+
+#[test]
+pub fn test_inspect_success_before_consumer() {
+    let (f, c) = Future::<i32, ()>::pair();
+    let (tx1, rx) = channel();
+    let tx2 = tx1.clone();
+
+    f.inspect(move |res| {
+        tx1.send(*res.as_ref().unwrap()).unwrap();
+    }).receive(move |res| {
+        tx2.send(res.unwrap() + 1).unwrap();
+    });
+
+    c.complete(123);
+
+    assert_eq!(123, rx.recv().unwrap());
+    assert_eq!(124, rx.recv().unwrap());
+}
+
+#[test]
+pub fn test_inspect_error_invoked_once() {
+    let (f, c) = Future::<i32, &'static str>::pair();
+    let cnt = Arc::new(AtomicUint::new(0));
+    let cnt2 = cnt.clone();
+
+    let f = f.inspect(move |res| {
+        assert!(res.is_err());
+        cnt2.fetch_add(1, Relaxed);
+    });
+
+    c.fail("nope");
+
+    assert_eq!("nope", f.await().unwrap_err().unwrap());
+    assert_eq!(1, cnt.load(Relaxed));
+}
@@ -0,0 +1,37 @@
+use syncbox::util::async::*;
+use std::sync::mpsc::channel;
+
+#[test]
+pub fn test_into_sender_complete() {
+    let (f, c) = Future::<i32, ()>::pair();
+    let (tx, rx) = channel();
+
+    f.into_sender(tx);
+    assert!(rx.try_recv().is_err());
+
+    c.complete(123);
+    assert_eq!(123, rx.recv().unwrap().unwrap());
+}
+
+#[test]
+pub fn test_into_sender_fail() {
+    let (f, c) = Future::<i32, &'static str>::pair();
+    let (tx, rx) = channel();
+
+    f.into_sender(tx);
+    c.fail("nope");
+
+    assert_eq!("nope", rx.recv().unwrap().unwrap_err().unwrap());
+}
+
+#[test]
+pub fn test_into_sender_disconnected_receiver() {
+    let (f, c) = Future::<i32, ()>::pair();
+    let (tx, rx) = channel();
+
+    f.into_sender(tx);
+    drop(rx);
+
+    // Does not panic
+    c.complete(123);
+}
@@ -0,0 +1,53 @@
+use syncbox::util::async::*;
+use std::sync::mpsc::channel;
+
+#[test]
+pub fn test_map_both_ok() {
+    let (f, c) = Future::<uint, ()>::pair();
+    let f = f.map_both(move |res| res.map(|v| v * 2));
+
+    c.complete(123);
+    assert_eq!(246, f.await().unwrap());
+}
+
+#[test]
+pub fn test_map_both_cancellation_to_value() {
+    let (f, c) = Future::<int, ()>::pair();
+
+    let f = f.map_both(move |res| {
+        match res {
+            Err(ref e) if e.is_cancellation() => Ok(-1),
+            res => res,
+        }
+    });
+
+    drop(c);
+    assert_eq!(-1, f.await().unwrap());
+}
+
+#[test]
+pub fn test_map_both_value_to_error() {
+    let (f, c) = Future::<uint, &'static str>::pair();
+    let f = f.map_both(move |_| -> AsyncResult<uint, &'static str> {
+        Err(AsyncError::wrap("nope"))
+    });
+
+    c.complete(123);
+    assert_eq!("nope", f.await().unwrap_err().unwrap());
+}
+
+#[test]
+pub fn test_map_both_is_lazy() {
+    let (f, c) = Future::<uint, ()>::pair();
+    let (tx, rx) = channel();
+
+    c.receive(move |c| tx.send(c.is_ok()).unwrap());
+
+    let f = f.map_both(move |res| res);
+
+    // Interest has not been registered yet
+    assert!(rx.try_recv().is_err());
+
+    f.receive(move |_| {});
+    assert!(rx.recv().unwrap());
+}
@@ -0,0 +1,41 @@
+use syncbox::util::async::*;
+use std::sync::mpsc::channel;
+
+#[test]
+pub fn test_map_err_success_passes_through() {
+    let (f, c) = Future::<i32, &'static str>::pair();
+    let (tx, rx) = channel();
+
+    f.map_err(move |e| e.len()).receive(move |res| {
+        tx.send(res.unwrap()).unwrap();
+    });
+
+    c.complete(123);
+    assert_eq!(123, rx.recv().unwrap());
+}
+
+#[test]
+pub fn test_map_err_rewrites_execution_error() {
+    let (f, c) = Future::<i32, &'static str>::pair();
+    let (tx, rx) = channel();
+
+    f.map_err(move |e| e.len()).receive(move |res| {
+        tx.send(res.unwrap_err().unwrap()).unwrap();
+    });
+
+    c.fail("nope");
+    assert_eq!(4, rx.recv().unwrap());
+}
+
+#[test]
+pub fn test_map_err_preserves_cancellation() {
+    let (f, c) = Future::<i32, &'static str>::pair();
+    let (tx, rx) = channel();
+
+    f.map_err(move |_| -> uint { panic!("nope") }).receive(move |res| {
+        tx.send(res.unwrap_err().is_cancellation()).unwrap();
+    });
+
+    drop(c);
+    assert!(rx.recv().unwrap());
+}
@@ -0,0 +1,57 @@
+use syncbox::util::async::*;
+use std::sync::mpsc::channel;
+
+#[test]
+pub fn test_map_result_ok() {
+    let (f, c) = Future::<&'static str, uint>::pair();
+    let (tx, rx) = channel();
+
+    f.map_result(move |s| Ok(s.len())).receive(move |res| {
+        tx.send(res.unwrap()).unwrap();
+    });
+
+    c.complete("hello");
+    assert_eq!(5, rx.recv().unwrap());
+}
+
+#[test]
+pub fn test_map_result_err() {
+    let (f, c) = Future::<&'static str, uint>::pair();
+    let (tx, rx) = channel();
+
+    f.map_result(move |s| -> Result<(), uint> { Err(s.len()) }).receive(move |res| {
+        tx.send(res.unwrap_err().unwrap()).unwrap();
+    });
+
+    c.complete("hello");
+    assert_eq!(5, rx.recv().unwrap());
+}
+
+#[test]
+pub fn test_map_result_upstream_err() {
+    let (f, c) = Future::<&'static str, uint>::pair();
+    let (tx, rx) = channel();
+
+    f.map_result(move |_| -> Result<(), uint> { panic!("nope") }).receive(move |res| {
+        tx.send(res.unwrap_err().unwrap()).unwrap();
+    });
+
+    c.fail(123);
+    assert_eq!(123, rx.recv().unwrap());
+}
+
+#[test]
+pub fn test_map_result_is_lazy() {
+    let (f, c) = Future::<&'static str, uint>::pair();
+    let (tx, rx) = channel();
+
+    c.receive(move |c| tx.send(c.is_ok()).unwrap());
+
+    let f = f.map_result(move |s| Ok(s.len()));
+
+    // Interest has not been registered yet
+    assert!(rx.try_recv().is_err());
+
+    f.receive(move |_| {});
+    assert!(rx.recv().unwrap());
+}
@@ -0,0 +1,29 @@
+use syncbox::util::async::*;
+use std::sync::mpsc::channel;
+
+#[test]
+pub fn test_of_is_ready() {
+    let f = Future::<i32, ()>::of(5);
+
+    assert!(f.is_ready());
+    assert!(!f.is_err());
+    assert_eq!(5, f.poll().unwrap().unwrap());
+}
+
+#[test]
+pub fn test_of_receive_is_synchronous() {
+    let (tx, rx) = channel();
+
+    Future::<i32, ()>::of(5).receive(move |res| {
+        tx.send(res.unwrap()).unwrap();
+    });
+
+    // The callback has been invoked before `receive` returned
+    assert_eq!(5, rx.try_recv().unwrap());
+}
+
+#[test]
+pub fn test_of_map_await() {
+    let f = Future::<i32, ()>::of(5).map(|x| x + 1);
+    assert_eq!(6, f.await().unwrap());
+}
@@ -0,0 +1,34 @@
+use syncbox::util::ThreadPool;
+use syncbox::util::async::*;
+use std::sync::mpsc::channel;
+use std::thread::{self, Thread};
+
+#[test]
+pub fn test_on_executor_receive_runs_on_executor() {
+    let (f, c) = Future::<uint, ()>::pair();
+    let (tx, rx) = channel();
+
+    f.on_executor(ThreadPool::single_thread()).receive(move |res| {
+        let name = Thread::current().name().map(|s| s.to_string());
+        tx.send((res.unwrap(), name)).unwrap();
+    });
+
+    // Complete the future from a named thread
+    thread::Builder::new().name("completer".to_string()).spawn(move || {
+        c.complete(123);
+    });
+
+    let (val, name) = rx.recv().unwrap();
+
+    assert_eq!(123, val);
+    assert!(name != Some("completer".to_string()));
+}
+
+#[test]
+pub fn test_on_executor_propagates_error() {
+    let (f, c) = Future::<uint, &'static str>::pair();
+    let f = f.on_executor(ThreadPool::single_thread());
+
+    c.fail("nope");
+    assert_eq!("nope", f.await().unwrap_err().unwrap());
+}
@@ -47,3 +47,113 @@ pub fn test_or_else_complete_before_receive() {
 
     assert_eq!(rx.recv().unwrap(), "caught");
 }
+
+#[test]
+pub fn test_or_second_success_async() {
+    let (f1, c1) = Future::<&'static str, ()>::pair();
+    let (f2, c2) = Future::<&'static str, ()>::pair();
+    let (tx, rx) = channel();
+
+    f1.or(f2).receive(move |res| {
+        tx.send(res.unwrap()).unwrap();
+    });
+
+    // Interest has been registered on both futures
+    assert!(c1.is_ready());
+    assert!(c2.is_ready());
+
+    c2.complete("second");
+    assert_eq!("second", rx.recv().unwrap());
+
+    // The first future was canceled
+    assert!(c1.await().unwrap_err().is_cancellation());
+}
+
+#[test]
+pub fn test_or_first_fails_waits_for_second() {
+    let (f1, c1) = Future::<&'static str, i32>::pair();
+    let (f2, c2) = Future::<&'static str, i32>::pair();
+    let (tx, rx) = channel();
+
+    f1.or(f2).receive(move |res| {
+        tx.send(res.unwrap()).unwrap();
+    });
+
+    c1.fail(123);
+    assert!(rx.try_recv().is_err());
+
+    c2.complete("second");
+    assert_eq!("second", rx.recv().unwrap());
+}
+
+#[test]
+pub fn test_or_both_fail() {
+    let (f1, c1) = Future::<&'static str, i32>::pair();
+    let (f2, c2) = Future::<&'static str, i32>::pair();
+    let (tx, rx) = channel();
+
+    f1.or(f2).receive(move |res| {
+        tx.send(res.unwrap_err().unwrap()).unwrap();
+    });
+
+    c2.fail(2);
+    assert!(rx.try_recv().is_err());
+
+    c1.fail(1);
+    assert_eq!(1, rx.recv().unwrap());
+}
+
+#[test]
+pub fn test_or_else_complete_after_receive() {
+    let (f, c) = Future::<uint, &'static str>::pair();
+    let (tx, rx) = channel();
+
+    f.or_else(move |e| {
+        Ok(e.unwrap().len())
+    }).receive(move |res| {
+        tx.send(res.unwrap()).unwrap();
+    });
+
+    assert!(c.is_ready());
+    c.fail("nope");
+
+    assert_eq!(4, rx.recv().unwrap());
+}
+
+#[test]
+pub fn test_or_else_fallback_not_invoked_on_success() {
+    let (f, c) = Future::<&'static str, ()>::pair();
+    let (tx, rx) = channel();
+
+    f.or_else(move |_| -> Future<&'static str, ()> {
+        panic!("fallback should not be constructed");
+    }).receive(move |res| {
+        tx.send(res.unwrap()).unwrap();
+    });
+
+    c.complete("zomg");
+    assert_eq!("zomg", rx.recv().unwrap());
+}
+
+#[test]
+pub fn test_or_else_fallback_future_async() {
+    let (f1, c1) = Future::<&'static str, &'static str>::pair();
+    let (f2, c2) = Future::<&'static str, &'static str>::pair();
+    let (tx, rx) = channel();
+
+    f1.or_else(move |e| {
+        assert_eq!("first", e.unwrap());
+        f2
+    }).receive(move |res| {
+        tx.send(res.unwrap_err().unwrap()).unwrap();
+    });
+
+    // Interest is only registered on the fallback once the source fails
+    assert!(!c2.is_ready());
+    c1.fail("first");
+    assert!(c2.is_ready());
+
+    // A failure in the fallback propagates through unchanged
+    c2.fail("second");
+    assert_eq!("second", rx.recv().unwrap());
+}
@@ -0,0 +1,75 @@
+use syncbox::util::async::*;
+use super::spawn;
+
+#[test]
+pub fn test_poll_before_complete() {
+    let (f, c) = Future::<i32, ()>::pair();
+
+    let f = f.poll().unwrap_err();
+    assert!(!f.is_ready());
+
+    c.complete(123);
+    assert_eq!(123, f.poll().unwrap().unwrap());
+}
+
+#[test]
+pub fn test_poll_after_fail() {
+    let (f, c) = Future::<i32, &'static str>::pair();
+
+    c.fail("nope");
+    assert_eq!("nope", f.poll().unwrap().unwrap_err().unwrap());
+}
+
+#[test]
+pub fn test_poll_complete_from_other_thread() {
+    let (mut f, c) = Future::<i32, ()>::pair();
+
+    spawn(move || c.complete(123));
+
+    loop {
+        match f.poll() {
+            Ok(res) => {
+                assert_eq!(123, res.unwrap());
+                break;
+            }
+            Err(rest) => f = rest,
+        }
+    }
+}
+
+#[test]
+pub fn test_is_ready_complete_from_other_thread() {
+    let (f, c) = Future::<i32, ()>::pair();
+
+    spawn(move || c.complete(123));
+
+    while !f.is_ready() {
+    }
+
+    assert!(!f.is_err());
+    assert!(!f.is_canceled());
+    assert_eq!(123, f.poll().unwrap().unwrap());
+}
+
+#[test]
+pub fn test_is_canceled() {
+    let (f, c) = Future::<i32, ()>::pair();
+
+    assert!(!f.is_canceled());
+
+    drop(c);
+
+    assert!(f.is_ready());
+    assert!(f.is_err());
+    assert!(f.is_canceled());
+}
+
+#[test]
+pub fn test_is_canceled_after_fail() {
+    let (f, c) = Future::<i32, ()>::pair();
+
+    c.fail(());
+
+    assert!(f.is_err());
+    assert!(!f.is_canceled());
+}
@@ -1,5 +1,6 @@
 use syncbox::util::async::*;
-use super::{spawn, sleep};
+use syncbox::util::Run;
+use super::{spawn, sleep, ManualRun};
 use std::sync::mpsc::channel;
 use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, AtomicUint};
@@ -9,12 +10,32 @@ use std::sync::atomic::Ordering::Relaxed;
 pub fn test_complete_before_receive() {
     let (f, c) = Future::<&'static str, ()>::pair();
     let (tx, rx) = channel();
+    let run = ManualRun::new();
 
-    spawn(move || c.complete("zomg"));
+    run.run(move || c.complete("zomg"));
+
+    // The future is completed before the callback is registered
+    assert_eq!(1, run.run_pending());
 
-    sleep(50);
     f.receive(move |v| tx.send(v.unwrap()).unwrap());
-    assert_eq!(rx.recv().unwrap(), "zomg");
+    assert_eq!(rx.try_recv().unwrap(), "zomg");
+}
+
+#[test]
+pub fn test_receive_defer_runs_on_executor() {
+    let (f, c) = Future::<&'static str, ()>::pair();
+    let (tx, rx) = channel();
+    let run = ManualRun::new();
+
+    f.receive_defer(move |v| tx.send(v.unwrap()).unwrap(), run.clone());
+    c.complete("zomg");
+
+    // The callback is queued on the executor rather than invoked inline
+    assert!(rx.try_recv().is_err());
+    assert_eq!(1, run.pending());
+
+    run.run_pending();
+    assert_eq!(rx.try_recv().unwrap(), "zomg");
 }
 
 #[test]
@@ -266,5 +287,106 @@ pub fn test_producer_fail_consumer_receive() {
 }
 
 #[test]
-pub fn test_panic_cancels_future() {
+pub fn test_producer_fail_before_consumer_receive() {
+    let (f, c) = Future::<uint, &'static str>::pair();
+    let (tx, rx) = channel();
+
+    c.fail("nope");
+
+    f.receive(move |res| {
+        let err = res.unwrap_err();
+        assert!(err.is_execution_error());
+        tx.send(err.unwrap()).unwrap();
+    });
+
+    assert_eq!("nope", rx.recv().unwrap());
+}
+
+#[test]
+pub fn test_lazy_producer_fail_consumer_receive() {
+    let f = Future::<uint, &'static str>::lazy(move || Err(AsyncError::wrap("nope")));
+    let (tx, rx) = channel();
+
+    f.receive(move |res| {
+        tx.send(res.unwrap_err().unwrap()).unwrap();
+    });
+
+    assert_eq!("nope", rx.recv().unwrap());
+}
+
+#[test]
+pub fn test_complete_is_ready_after_consumer_receive() {
+    let (f, c) = Future::<uint, ()>::pair();
+
+    assert!(!c.is_ready());
+
+    f.receive(move |res| {
+        assert_eq!(123, res.unwrap());
+    });
+
+    assert!(c.is_ready());
+    c.complete(123);
+}
+
+#[test]
+pub fn test_complete_is_ready_after_consumer_await() {
+    let (f, c) = Future::<uint, ()>::pair();
+    let (tx, rx) = channel();
+
+    assert!(!c.is_ready());
+
+    spawn(move || tx.send(f.await().unwrap()).unwrap());
+
+    // Wait for the consumer to block
+    let c = c.await().unwrap();
+
+    assert!(c.is_ready());
+    c.complete(123);
+
+    assert_eq!(123, rx.recv().unwrap());
+}
+
+#[test]
+pub fn test_panic_fails_future() {
+    let (f, c) = Future::<uint, ()>::pair();
+
+    let res = f.map(|_| -> uint { panic!("boom") });
+
+    // The map closure runs on the thread completing the source future, so
+    // the panic unwinds that thread.
+    spawn(move || c.complete(1));
+
+    let err = res.await().unwrap_err();
+
+    assert!(err.is_panic());
+    assert!(!err.is_cancellation());
+}
+
+#[test]
+pub fn test_panic_in_and_then_fails_future() {
+    let (f, c) = Future::<uint, ()>::pair();
+
+    let res = f.and_then(|_| -> Result<uint, AsyncError<()>> { panic!("boom") });
+
+    spawn(move || c.complete(1));
+
+    let err = res.await().unwrap_err();
+
+    // The panic is reported as its own error, not as a cancellation
+    assert!(err.is_panic());
+    assert!(!err.is_cancellation());
+    assert!(!err.is_execution_error());
+}
+
+#[test]
+pub fn test_panic_in_receive_callback_fails_future() {
+    let (f, c) = Future::<uint, ()>::pair();
+    let (tx, rx) = channel();
+
+    f.and_then(|_| -> Result<uint, AsyncError<()>> { panic!("boom") })
+        .receive(move |res| tx.send(res.unwrap_err().is_panic()).unwrap());
+
+    spawn(move || c.complete(1));
+
+    assert!(rx.recv().unwrap());
 }
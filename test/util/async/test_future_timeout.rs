@@ -0,0 +1,75 @@
+use syncbox::util::async::*;
+use super::{spawn, sleep, millis};
+
+#[test]
+pub fn test_timeout_value_in_time() {
+    let (f, c) = Future::<&'static str, ()>::pair();
+
+    spawn(move || {
+        sleep(20);
+        c.complete("zomg");
+    });
+
+    assert_eq!("zomg", f.timeout(millis(500)).await().unwrap());
+}
+
+#[test]
+pub fn test_timeout_value_already_realized() {
+    let f = Future::<&'static str, ()>::of("zomg");
+    assert_eq!("zomg", f.timeout(millis(500)).await().unwrap());
+}
+
+#[test]
+pub fn test_timeout_error_in_time() {
+    let f = Future::<(), &'static str>::error("nope");
+    let err = f.timeout(millis(500)).await().unwrap_err();
+
+    assert!(err.is_execution_error());
+    assert!(!err.is_timeout());
+}
+
+#[test]
+pub fn test_timeout_elapses() {
+    let (f, c) = Future::<&'static str, ()>::pair();
+
+    let err = f.timeout(millis(50)).await().unwrap_err();
+
+    assert!(err.is_timeout());
+    assert!(!err.is_cancellation());
+    assert!(!err.is_execution_error());
+
+    // The source future has been canceled
+    assert!(c.await().is_err());
+}
+
+#[test]
+pub fn test_timeout_through_map() {
+    let (f, _c) = Future::<uint, ()>::pair();
+
+    let err = f.timeout(millis(20))
+        .map(|v| v + 1)
+        .await().unwrap_err();
+
+    assert!(err.is_timeout());
+}
+
+#[test]
+pub fn test_timeout_through_and_then() {
+    let (f, _c) = Future::<uint, ()>::pair();
+
+    let err = f.timeout(millis(20))
+        .and_then(|v| Ok(v + 1))
+        .await().unwrap_err();
+
+    assert!(err.is_timeout());
+}
+
+#[test]
+pub fn test_timeout_through_join() {
+    let (f, _c) = Future::<uint, ()>::pair();
+
+    let err = join((f.timeout(millis(20)), Future::of(1u)))
+        .await().unwrap_err();
+
+    assert!(err.is_timeout());
+}
@@ -0,0 +1,43 @@
+use syncbox::util::async::*;
+use std::sync::mpsc::channel;
+
+#[test]
+pub fn test_unwrap_or_success() {
+    let (f, c) = Future::<i32, ()>::pair();
+    let f = f.unwrap_or(42);
+
+    c.complete(1);
+    assert_eq!(1, f.await().unwrap());
+}
+
+#[test]
+pub fn test_unwrap_or_failure() {
+    let (f, c) = Future::<i32, ()>::pair();
+    let f = f.unwrap_or(42);
+
+    c.fail(());
+    assert_eq!(42, f.await().unwrap());
+}
+
+#[test]
+pub fn test_unwrap_or_cancellation() {
+    let (f, c) = Future::<i32, ()>::pair();
+    let f = f.unwrap_or(42);
+
+    drop(c);
+    assert_eq!(42, f.await().unwrap());
+}
+
+#[test]
+pub fn test_unwrap_or_is_lazy() {
+    let (f, c) = Future::<i32, ()>::pair();
+    let (tx, rx) = channel();
+
+    c.receive(move |c| tx.send(c.is_ok()).unwrap());
+
+    let f = f.unwrap_or(42);
+    assert!(rx.try_recv().is_err());
+
+    f.receive(move |_| {});
+    assert!(rx.recv().unwrap());
+}
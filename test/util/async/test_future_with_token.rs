@@ -0,0 +1,71 @@
+use syncbox::util::async::*;
+use std::sync::mpsc::channel;
+
+#[test]
+pub fn test_with_token_cancels_all_bound_futures() {
+    let source = CancelSource::new();
+    let token = source.token();
+
+    let (f1, _c1) = Future::<uint, ()>::pair();
+    let (f2, _c2) = Future::<uint, ()>::pair();
+    let (f3, _c3) = Future::<uint, ()>::pair();
+
+    let f1 = f1.with_token(token.clone());
+    let f2 = f2.with_token(token.clone());
+    let f3 = f3.with_token(token.clone());
+
+    source.cancel();
+
+    assert!(token.is_canceled());
+    assert!(f1.await().unwrap_err().is_cancellation());
+    assert!(f2.await().unwrap_err().is_cancellation());
+    assert!(f3.await().unwrap_err().is_cancellation());
+}
+
+#[test]
+pub fn test_with_token_after_cancel() {
+    let source = CancelSource::new();
+    source.cancel();
+
+    let f = Future::<uint, ()>::of(123).with_token(source.token());
+    assert!(f.await().unwrap_err().is_cancellation());
+}
+
+#[test]
+pub fn test_with_token_completes_before_cancel() {
+    let source = CancelSource::new();
+    let (f, c) = Future::<uint, &'static str>::pair();
+    let (g, d) = Future::<uint, &'static str>::pair();
+
+    let f = f.with_token(source.token());
+    let g = g.with_token(source.token());
+
+    c.complete(123);
+    d.fail("nope");
+
+    assert_eq!(123, f.await().unwrap());
+    assert_eq!("nope", g.await().unwrap_err().unwrap());
+
+    // Canceling once the futures are realized has no effect
+    source.cancel();
+}
+
+#[test]
+pub fn test_with_token_cancels_upstream() {
+    let source = CancelSource::new();
+    let (f, c) = Future::<uint, ()>::pair();
+    let (tx, rx) = channel();
+
+    f.with_token(source.token()).receive(move |res| {
+        tx.send(res.unwrap_err().is_cancellation()).unwrap();
+    });
+
+    // The consumer is waiting on the upstream value
+    let c = c.await().unwrap();
+    assert!(!c.is_canceled());
+
+    source.cancel();
+
+    assert!(rx.recv().unwrap());
+    assert!(c.is_canceled());
+}
@@ -76,11 +76,8 @@ pub fn test_join_errors_on_failure() {
     assert_eq!("win", rx.try_recv().unwrap());
 }
 
-/*
-
-    Test is blocked by a Rust bug
-    https://github.com/rust-lang/rust/issues/21080
-
+// Joining a tuple of three is blocked by a Rust bug
+// (https://github.com/rust-lang/rust/issues/21080), use join3 instead.
 #[test]
 pub fn test_joining_three_futures_async() {
     let (f1, c1) = Future::<i32, ()>::pair();
@@ -88,7 +85,7 @@ pub fn test_joining_three_futures_async() {
     let (f3, c3) = Future::<i32, ()>::pair();
     let (tx, rx) = channel::<(i32, i32, i32)>();
 
-    join((f1, f2, f3)).receive(move |res: AsyncResult<(i32, i32, i32), ()>| {
+    join3(f1, f2, f3).receive(move |res: AsyncResult<(i32, i32, i32), ()>| {
         tx.send(res.unwrap()).unwrap();
     });
 
@@ -103,4 +100,145 @@ pub fn test_joining_three_futures_async() {
 
     assert_eq!(rx.recv().unwrap(), (1, 2, 3));
 }
-*/
+
+#[test]
+pub fn test_join3_errors_on_failure() {
+    let (f1, _c1) = Future::<i32, &'static str>::pair();
+    let (f2, _c2) = Future::<i32, &'static str>::pair();
+    let (f3, c3) = Future::<i32, &'static str>::pair();
+    let (tx, rx) = channel();
+
+    join3(f1, f2, f3).receive(move |res| {
+        tx.send(res.unwrap_err().unwrap()).unwrap();
+    });
+
+    c3.fail("nope");
+    assert_eq!("nope", rx.try_recv().unwrap());
+}
+
+#[test]
+pub fn test_joining_four_futures_async() {
+    let (f1, c1) = Future::<i32, ()>::pair();
+    let (f2, c2) = Future::<&'static str, ()>::pair();
+    let (f3, c3) = Future::<i32, ()>::pair();
+    let (f4, c4) = Future::<bool, ()>::pair();
+
+    let f = join4(f1, f2, f3, f4);
+
+    c4.complete(true);
+    c2.complete("two");
+    c1.complete(1);
+    c3.complete(3);
+
+    assert_eq!((1, "two", 3, true), f.await().unwrap());
+}
+
+/*
+ *
+ * ===== join(Vec<...>) =====
+ *
+ */
+
+#[test]
+pub fn test_joining_empty_vec() {
+    let futures: Vec<Future<i32, ()>> = vec![];
+    let vals = join(futures).await().unwrap();
+
+    assert!(vals.is_empty());
+}
+
+#[test]
+pub fn test_joining_vec_out_of_order() {
+    let (f1, c1) = Future::<i32, ()>::pair();
+    let (f2, c2) = Future::<i32, ()>::pair();
+    let (f3, c3) = Future::<i32, ()>::pair();
+    let (tx, rx) = channel();
+
+    join(vec![f1, f2, f3]).receive(move |res| {
+        tx.send(res.unwrap()).unwrap();
+    });
+
+    c3.complete(3);
+    assert!(rx.try_recv().is_err());
+
+    c1.complete(1);
+    assert!(rx.try_recv().is_err());
+
+    c2.complete(2);
+    assert_eq!([1, 2, 3].as_slice(), rx.recv().unwrap().as_slice());
+}
+
+#[test]
+pub fn test_joining_vec_with_failure() {
+    let (f1, c1) = Future::<i32, &'static str>::pair();
+    let (f2, c2) = Future::<i32, &'static str>::pair();
+    let (tx, rx) = channel();
+
+    join(vec![f1, f2]).receive(move |res| {
+        tx.send(res.unwrap_err().unwrap()).unwrap();
+    });
+
+    c2.fail("nope");
+    assert_eq!("nope", rx.try_recv().unwrap());
+
+    drop(c1);
+}
+
+#[test]
+pub fn test_joining_vec_is_lazy() {
+    let (f1, c1) = Future::<i32, ()>::pair();
+    let f = join(vec![f1]);
+
+    // No interest has been registered yet
+    assert!(!c1.is_ready());
+
+    c1.complete(1);
+    assert_eq!([1].as_slice(), f.await().unwrap().as_slice());
+}
+
+#[test]
+pub fn test_join_method_async() {
+    let (f1, c1) = Future::<i32, ()>::pair();
+    let (f2, c2) = Future::<&'static str, ()>::pair();
+    let (tx, rx) = channel();
+
+    f1.join(f2).receive(move |res| {
+        tx.send(res.unwrap()).unwrap();
+    });
+
+    c2.complete("hello");
+    assert!(rx.try_recv().is_err());
+
+    c1.complete(1);
+    assert_eq!(rx.recv().unwrap(), (1, "hello"));
+}
+
+#[test]
+pub fn test_join_method_errors_on_failure() {
+    let (f1, _c) = Future::<i32, &'static str>::pair();
+    let (f2, c2) = Future::<i32, &'static str>::pair();
+    let (tx, rx) = channel();
+
+    f1.join(f2).receive(move |res| {
+        tx.send(res.unwrap_err().unwrap()).unwrap();
+    });
+
+    // Fails without waiting on the first future
+    c2.fail("nope");
+    assert_eq!("nope", rx.try_recv().unwrap());
+}
+
+#[test]
+pub fn test_join_method_is_lazy() {
+    let (f1, c1) = Future::<i32, ()>::pair();
+    let (f2, c2) = Future::<i32, ()>::pair();
+    let f = f1.join(f2);
+
+    // No interest has been registered yet
+    assert!(!c1.is_ready());
+    assert!(!c2.is_ready());
+
+    c1.complete(1);
+    c2.complete(2);
+    assert_eq!((1, 2), f.await().unwrap());
+}
@@ -0,0 +1,130 @@
+use syncbox::util::async::*;
+use std::sync::Arc;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering::Relaxed;
+use std::sync::mpsc::channel;
+use super::{millis, sleep};
+
+#[test]
+pub fn test_retry_until_success() {
+    let count = Arc::new(AtomicUsize::new(0));
+    let c = count.clone();
+
+    let f = retry(move || {
+        if c.fetch_add(1, Relaxed) < 2 {
+            Future::error("nope")
+        } else {
+            Future::of("done")
+        }
+    }, 3);
+
+    assert_eq!("done", f.await().unwrap());
+    assert_eq!(3, count.load(Relaxed));
+}
+
+#[test]
+pub fn test_retry_attempts_exhausted() {
+    let count = Arc::new(AtomicUsize::new(0));
+    let c = count.clone();
+
+    let f = retry(move || {
+        c.fetch_add(1, Relaxed);
+        Future::<(), &'static str>::error("nope")
+    }, 2);
+
+    assert_eq!("nope", f.await().unwrap_err().unwrap());
+    assert_eq!(2, count.load(Relaxed));
+}
+
+#[test]
+pub fn test_retry_does_not_retry_cancellation() {
+    let count = Arc::new(AtomicUsize::new(0));
+    let c = count.clone();
+
+    let f = retry(move || {
+        c.fetch_add(1, Relaxed);
+        Future::<(), ()>::canceled()
+    }, 3);
+
+    assert!(f.await().unwrap_err().is_cancellation());
+    assert_eq!(1, count.load(Relaxed));
+}
+
+#[test]
+pub fn test_retry_is_lazy() {
+    let count = Arc::new(AtomicUsize::new(0));
+    let c = count.clone();
+
+    let f = retry(move || {
+        c.fetch_add(1, Relaxed);
+        Future::<(), ()>::of(())
+    }, 3);
+
+    assert_eq!(0, count.load(Relaxed));
+    assert!(f.await().is_ok());
+    assert_eq!(1, count.load(Relaxed));
+}
+
+#[test]
+pub fn test_retry_with_backoff_waits_longer_each_time() {
+    let count = Arc::new(AtomicUsize::new(0));
+    let c = count.clone();
+    let (tx, rx) = channel();
+
+    // Attempts happen at roughly 0ms, 50ms and 150ms
+    retry_with_backoff(move || {
+        if c.fetch_add(1, Relaxed) < 2 {
+            Future::error("nope")
+        } else {
+            Future::of("done")
+        }
+    }, 3, millis(50), 2.0).receive(move |res| {
+        tx.send(res.unwrap()).unwrap();
+    });
+
+    sleep(25);
+    assert_eq!(1, count.load(Relaxed));
+
+    sleep(50);
+    assert_eq!(2, count.load(Relaxed));
+
+    // The second wait is twice as long as the first one
+    sleep(50);
+    assert_eq!(2, count.load(Relaxed));
+
+    assert_eq!("done", rx.recv().unwrap());
+    assert_eq!(3, count.load(Relaxed));
+}
+
+#[test]
+pub fn test_retry_with_backoff_attempts_exhausted() {
+    let count = Arc::new(AtomicUsize::new(0));
+    let c = count.clone();
+
+    let f = retry_with_backoff(move || {
+        c.fetch_add(1, Relaxed);
+        Future::<(), &'static str>::error("nope")
+    }, 2, millis(10), 2.0);
+
+    assert_eq!("nope", f.await().unwrap_err().unwrap());
+    assert_eq!(2, count.load(Relaxed));
+}
+
+#[test]
+pub fn test_retry_with_backoff_canceled_during_wait() {
+    let count = Arc::new(AtomicUsize::new(0));
+    let c = count.clone();
+
+    let cancel = retry_with_backoff(move || {
+        c.fetch_add(1, Relaxed);
+        Future::<(), ()>::error(())
+    }, 3, millis(20), 2.0).receive(move |_| {});
+
+    assert_eq!(1, count.load(Relaxed));
+
+    // The future is handed back, then dropped
+    drop(cancel.cancel());
+
+    sleep(100);
+    assert_eq!(1, count.load(Relaxed));
+}
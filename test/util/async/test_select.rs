@@ -76,3 +76,102 @@ pub fn test_selecting_two_completed_futures_async() {
     assert_eq!(123, f1.expect().unwrap());
     assert_eq!(234, f2.expect().unwrap());
 }
+
+/*
+ *
+ * ===== select(Vec<...>) =====
+ *
+ */
+
+#[test]
+pub fn test_selecting_vec_of_futures_async() {
+    let (f1, c1) = Future::<i32, ()>::pair();
+    let (f2, c2) = Future::<i32, ()>::pair();
+    let (f3, c3) = Future::<i32, ()>::pair();
+    let (tx, rx) = channel();
+
+    async::select(vec![f1, f2, f3]).receive(move |res| {
+        tx.send(res.unwrap()).unwrap();
+    });
+
+    c2.complete(234);
+
+    let (i, mut rest) = rx.recv().unwrap();
+    assert_eq!(1, i);
+
+    let f3 = rest.pop().unwrap();
+    let f2 = rest.pop().unwrap();
+    let f1 = rest.pop().unwrap();
+
+    assert_eq!(234, f2.expect().unwrap());
+    assert!(!f1.is_ready());
+    assert!(!f3.is_ready());
+
+    c1.complete(123);
+    c3.complete(345);
+
+    assert_eq!(123, f1.expect().unwrap());
+    assert_eq!(345, f3.expect().unwrap());
+}
+
+#[test]
+pub fn test_selecting_vec_of_completed_futures_async() {
+    let futures = vec![Future::<i32, ()>::of(123), Future::of(234)];
+    let (i, rest) = async::select(futures).await().unwrap();
+
+    assert_eq!(0, i);
+    assert_eq!(2, rest.len());
+}
+
+#[test]
+pub fn test_selecting_empty_vec() {
+    let futures: Vec<Future<i32, ()>> = vec![];
+    let res = async::select(futures).await();
+
+    assert!(res.unwrap_err().is_cancellation());
+}
+
+#[test]
+pub fn test_selecting_completed_futures_prefers_lowest_index() {
+    for _ in range(0, 100u) {
+        let (f1, _c1) = Future::<i32, ()>::pair();
+        let futures = vec![f1, Future::of(234), Future::of(345)];
+
+        let (i, _) = async::select(futures).await().unwrap();
+        assert_eq!(1, i);
+
+        let (i, _) = async::select((Future::<i32, ()>::of(123), Future::of(234))).await().unwrap();
+        assert_eq!(0, i);
+    }
+}
+
+#[test]
+pub fn test_select_macro_heterogeneous_futures() {
+    let (f1, c1) = Future::<i32, ()>::pair();
+    let (f2, c2) = Future::<String, ()>::pair();
+
+    let f = select! {
+        v = f1 => format!("int {}", v),
+        s = f2 => format!("string {}", s),
+    };
+
+    c2.complete("hello".to_string());
+    assert_eq!("string hello", f.await().unwrap().as_slice());
+
+    // The losing future was canceled
+    assert!(c1.await().is_err());
+}
+
+#[test]
+pub fn test_select_macro_error() {
+    let (f1, c1) = Future::<i32, &'static str>::pair();
+    let (f2, _c2) = Future::<String, &'static str>::pair();
+
+    let f = select! {
+        v = f1 => v,
+        s = f2 => s.len() as i32
+    };
+
+    c1.fail("nope");
+    assert_eq!("nope", f.await().unwrap_err().unwrap());
+}
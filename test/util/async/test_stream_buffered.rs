@@ -0,0 +1,78 @@
+use syncbox::util::async::*;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use super::{nums, spawn, sleep};
+
+#[test]
+pub fn test_stream_buffered_preserves_order() {
+    // Earlier futures take longer to complete
+    let stream = nums(0, 5).map(move |i| delayed(i, (5 - i) * 20));
+    let vals: Vec<uint> = stream.buffered(3).iter().collect();
+
+    assert_eq!([0, 1, 2, 3, 4].as_slice(), vals.as_slice());
+}
+
+#[test]
+pub fn test_stream_buffered_bounds_concurrency() {
+    let running = Arc::new(AtomicUsize::new(0));
+    let max = Arc::new(AtomicUsize::new(0));
+
+    let (r, m) = (running.clone(), max.clone());
+
+    // The futures are lazy, so they only run once interest is registered
+    let stream = nums(0, 10).map(move |i| {
+        let (r, m) = (r.clone(), m.clone());
+
+        Future::lazy(move || {
+            let (f, c) = Future::<uint, ()>::pair();
+
+            let curr = r.fetch_add(1, Ordering::SeqCst) + 1;
+
+            if curr > m.load(Ordering::SeqCst) {
+                m.store(curr, Ordering::SeqCst);
+            }
+
+            spawn(move || {
+                sleep(20);
+                r.fetch_sub(1, Ordering::SeqCst);
+                c.complete(i);
+            });
+
+            f
+        })
+    });
+
+    let vals: Vec<uint> = stream.buffered(3).iter().collect();
+
+    assert_eq!(10, vals.len());
+    assert_eq!(3, max.load(Ordering::SeqCst));
+    assert_eq!(0, running.load(Ordering::SeqCst));
+}
+
+#[test]
+pub fn test_stream_buffered_error() {
+    let stream = nums(0, 3).map(move |i| {
+        if i == 1 {
+            Future::error(())
+        } else {
+            Future::of(i)
+        }
+    });
+
+    let (v, rest) = stream.buffered(2).await().unwrap().unwrap();
+
+    assert_eq!(0, v);
+    assert!(rest.await().unwrap_err().is_execution_error());
+}
+
+// Returns a future that completes with `val` after `ms` milliseconds.
+fn delayed(val: uint, ms: uint) -> Future<uint, ()> {
+    let (f, c) = Future::pair();
+
+    spawn(move || {
+        sleep(ms);
+        c.complete(val);
+    });
+
+    f
+}
@@ -0,0 +1,32 @@
+use syncbox::util::async::*;
+use super::nums;
+
+#[test]
+pub fn test_stream_chain() {
+    let stream = nums(0, 2).chain(nums(5, 7));
+    let vals: Vec<uint> = stream.iter().collect();
+
+    assert_eq!([0, 1, 5, 6].as_slice(), vals.as_slice());
+}
+
+#[test]
+pub fn test_stream_chain_empty_first() {
+    let stream = nums(0, 0).chain(nums(5, 7));
+    let vals: Vec<uint> = stream.iter().collect();
+
+    assert_eq!([5, 6].as_slice(), vals.as_slice());
+}
+
+#[test]
+pub fn test_stream_chain_error_in_second() {
+    let (other, generate) = Stream::<uint, &'static str>::pair();
+
+    generate.receive(move |res| {
+        res.unwrap().fail("nope");
+    });
+
+    let first: Stream<uint, &'static str> = Future::of(None).as_stream();
+
+    let res = first.chain(other).await();
+    assert_eq!("nope", res.unwrap_err().unwrap());
+}
@@ -0,0 +1,31 @@
+use syncbox::util::async::*;
+use super::nums;
+
+#[test]
+pub fn test_stream_collect() {
+    let vals = nums(0, 5).collect().await().unwrap();
+    assert_eq!([0, 1, 2, 3, 4].as_slice(), vals.as_slice());
+}
+
+#[test]
+pub fn test_stream_collect_empty() {
+    let vals = nums(0, 0).collect().await().unwrap();
+    assert!(vals.is_empty());
+}
+
+#[test]
+pub fn test_stream_collect_error() {
+    let (stream, generate) = Stream::<uint, &'static str>::pair();
+
+    generate.receive(move |res| {
+        let generate = res.unwrap();
+        generate.send(1);
+
+        generate.receive(move |res| {
+            res.unwrap().fail("nope");
+        });
+    });
+
+    let res = stream.collect().await();
+    assert_eq!("nope", res.unwrap_err().unwrap());
+}
@@ -0,0 +1,29 @@
+use syncbox::util::async::*;
+use super::{produce, millis};
+
+#[test]
+pub fn test_stream_debounce_burst() {
+    // A burst of values, a pause, then a final value
+    let stream = produce(vec![(1, 0), (2, 5), (3, 5), (4, 150)]);
+    let vals: Vec<uint> = stream.debounce(millis(50)).iter().collect();
+
+    assert_eq!([3, 4].as_slice(), vals.as_slice());
+}
+
+#[test]
+pub fn test_stream_debounce_spaced_values() {
+    let stream = produce(vec![(1, 0), (2, 100), (3, 100)]);
+    let vals: Vec<uint> = stream.debounce(millis(20)).iter().collect();
+
+    assert_eq!([1, 2, 3].as_slice(), vals.as_slice());
+}
+
+#[test]
+pub fn test_stream_debounce_error() {
+    let (stream, generate) = Stream::<uint, &'static str>::pair();
+
+    generate.receive(move |g| g.unwrap().fail("nope"));
+
+    let res = stream.debounce(millis(20)).await();
+    assert_eq!("nope", res.unwrap_err().unwrap());
+}
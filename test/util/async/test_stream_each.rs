@@ -0,0 +1,27 @@
+use syncbox::util::async::*;
+use std::sync::mpsc::channel;
+use super::nums;
+
+#[test]
+pub fn test_stream_each() {
+    let (tx, rx) = channel();
+
+    let done = nums(0, 5).each(move |v| tx.send(v).unwrap());
+    assert!(done.await().is_ok());
+
+    // Every value has been processed by the time the future completes
+    let vals: Vec<uint> = range(0, 5).map(|_| rx.try_recv().unwrap()).collect();
+    assert_eq!([0, 1, 2, 3, 4].as_slice(), vals.as_slice());
+}
+
+#[test]
+pub fn test_stream_each_error() {
+    let (stream, generate) = Stream::<uint, &'static str>::pair();
+
+    generate.receive(move |res| {
+        res.unwrap().fail("nope");
+    });
+
+    let res = stream.each(move |_| panic!("unexpected value")).await();
+    assert_eq!("nope", res.unwrap_err().unwrap());
+}
@@ -0,0 +1,35 @@
+use syncbox::util::async::*;
+use super::nums;
+
+#[test]
+pub fn test_stream_filter() {
+    let stream = nums(0, 10).filter(|i| i % 2 == 0);
+    let vals: Vec<uint> = stream.iter().collect();
+
+    assert_eq!([0, 2, 4, 6, 8].as_slice(), vals.as_slice());
+}
+
+#[test]
+pub fn test_stream_filter_none_match() {
+    let stream = nums(0, 10).filter(|i| *i > 100);
+    let vals: Vec<uint> = stream.iter().collect();
+
+    assert!(vals.is_empty());
+}
+
+#[test]
+pub fn test_stream_filter_error() {
+    let (stream, generate) = Stream::<uint, &'static str>::pair();
+
+    generate.receive(move |res| {
+        let generate = res.unwrap();
+        generate.send(1);
+
+        generate.receive(move |res| {
+            res.unwrap().fail("nope");
+        });
+    });
+
+    let res = stream.filter(|i| *i > 1).await();
+    assert_eq!("nope", res.unwrap_err().unwrap());
+}
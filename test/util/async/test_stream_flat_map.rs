@@ -0,0 +1,17 @@
+use super::nums;
+
+#[test]
+pub fn test_stream_flat_map() {
+    let stream = nums(0, 3).flat_map(|i| nums(i * 10, i * 10 + 2));
+    let vals: Vec<uint> = stream.iter().collect();
+
+    assert_eq!([0, 1, 10, 11, 20, 21].as_slice(), vals.as_slice());
+}
+
+#[test]
+pub fn test_stream_flat_map_empty_inner() {
+    let stream = nums(0, 4).flat_map(|i| nums(i, if i % 2 == 0 { i } else { i + 1 }));
+    let vals: Vec<uint> = stream.iter().collect();
+
+    assert_eq!([1, 3].as_slice(), vals.as_slice());
+}
@@ -0,0 +1,39 @@
+use syncbox::util::async::*;
+use std::sync::Arc;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering::Relaxed;
+
+#[test]
+pub fn test_stream_from_iter() {
+    let stream = Stream::<uint, ()>::from_iter(vec![1, 2, 3].into_iter());
+    let vals: Vec<uint> = stream.iter().collect();
+
+    assert_eq!([1, 2, 3].as_slice(), vals.as_slice());
+}
+
+#[test]
+pub fn test_stream_from_empty_iter() {
+    let stream = Stream::<uint, ()>::from_iter(vec![].into_iter());
+    let vals: Vec<uint> = stream.iter().collect();
+
+    assert!(vals.is_empty());
+}
+
+#[test]
+pub fn test_stream_from_iter_is_lazy() {
+    let count = Arc::new(AtomicUsize::new(0));
+    let c = count.clone();
+
+    let iter = range(0, 10u).map(move |i| {
+        c.fetch_add(1, Relaxed);
+        i
+    });
+
+    let stream = Stream::<uint, ()>::from_iter(iter);
+    assert_eq!(0, count.load(Relaxed));
+
+    let vals: Vec<uint> = stream.take(2).iter().collect();
+
+    assert_eq!([0, 1].as_slice(), vals.as_slice());
+    assert_eq!(2, count.load(Relaxed));
+}
@@ -0,0 +1,59 @@
+use syncbox::util::LinkedQueue;
+use syncbox::util::async::*;
+use std::sync::mpsc::channel;
+use super::{spawn, sleep};
+
+#[test]
+pub fn test_stream_from_queue() {
+    let queue = LinkedQueue::new();
+
+    queue.put(1u);
+    queue.put(2u);
+    queue.put(3u);
+    queue.close();
+
+    let stream = Stream::<uint, ()>::from_queue(queue);
+    let vals: Vec<uint> = stream.iter().collect();
+
+    assert_eq!([1, 2, 3].as_slice(), vals.as_slice());
+}
+
+#[test]
+pub fn test_stream_from_queue_waits_for_values() {
+    let queue = LinkedQueue::new();
+    let producer = queue.clone();
+
+    spawn(move || {
+        for i in range(0, 3u) {
+            sleep(10);
+            producer.put(i);
+        }
+
+        producer.close();
+    });
+
+    let stream = Stream::<uint, ()>::from_queue(queue).map(|i| i * 2);
+    let vals: Vec<uint> = stream.iter().collect();
+
+    assert_eq!([0, 2, 4].as_slice(), vals.as_slice());
+}
+
+#[test]
+pub fn test_stream_from_queue_does_not_block_caller() {
+    let queue = LinkedQueue::new();
+    let (tx, rx) = channel();
+
+    // The queue is empty, so the test would hang here if registering
+    // interest blocked the caller
+    Stream::<uint, ()>::from_queue(queue.clone()).receive(move |res| {
+        match res {
+            Ok(Some((v, _))) => tx.send(v).unwrap(),
+            _ => panic!("nope"),
+        }
+    });
+
+    assert!(rx.try_recv().is_err());
+
+    queue.put(123);
+    assert_eq!(123, rx.recv().unwrap());
+}
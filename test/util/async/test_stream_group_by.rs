@@ -0,0 +1,40 @@
+use syncbox::util::async::*;
+use super::nums;
+
+#[test]
+pub fn test_stream_group_by_identity() {
+    let stream = Stream::<uint, ()>::from_iter(vec![1, 1, 2, 3, 3].into_iter())
+        .group_by(move |v| *v);
+
+    let groups: Vec<(uint, Vec<uint>)> = stream.iter().collect();
+
+    assert_eq!(vec![(1, vec![1, 1]), (2, vec![2]), (3, vec![3, 3])], groups);
+}
+
+#[test]
+pub fn test_stream_group_by_key() {
+    let groups: Vec<(uint, Vec<uint>)> = nums(0, 7)
+        .group_by(move |v| *v / 3)
+        .iter().collect();
+
+    assert_eq!(vec![(0, vec![0, 1, 2]), (1, vec![3, 4, 5]), (2, vec![6])], groups);
+}
+
+#[test]
+pub fn test_stream_group_by_empty() {
+    let groups: Vec<(uint, Vec<uint>)> = nums(0, 0)
+        .group_by(move |v| *v)
+        .iter().collect();
+
+    assert!(groups.is_empty());
+}
+
+#[test]
+pub fn test_stream_group_by_error() {
+    let (stream, generate) = Stream::<uint, &'static str>::pair();
+
+    generate.receive(move |g| g.unwrap().fail("nope"));
+
+    let res = stream.group_by(move |v| *v).await();
+    assert_eq!("nope", res.unwrap_err().unwrap());
+}
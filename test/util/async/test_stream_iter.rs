@@ -22,3 +22,25 @@ pub fn test_stream_iter_async_producer() {
     let vals: Vec<uint> = stream.iter().collect();
     assert_eq!([0, 1, 2, 3, 4].as_slice(), vals.as_slice());
 }
+
+#[test]
+#[should_fail(expected = "the stream failed")]
+pub fn test_stream_iter_failed_stream() {
+    let (stream, producer) = Stream::<uint, &'static str>::pair();
+
+    producer.receive(move |p| {
+        let p = p.unwrap();
+        p.send(1);
+
+        p.receive(move |p| {
+            p.unwrap().fail("nope");
+        });
+    });
+
+    let mut iter = stream.iter();
+
+    assert_eq!(Some(1), iter.next());
+
+    // The error surfaces instead of ending the iteration
+    iter.next();
+}
@@ -0,0 +1,66 @@
+use syncbox::util::LinkedQueue;
+use syncbox::util::async::*;
+use super::{nums, produce, spawn, sleep};
+
+#[test]
+pub fn test_stream_merge() {
+    let stream = nums(0, 3).merge(nums(10, 13));
+    let mut vals: Vec<uint> = stream.iter().collect();
+
+    vals.sort();
+    assert_eq!([0, 1, 2, 10, 11, 12].as_slice(), vals.as_slice());
+}
+
+#[test]
+pub fn test_stream_merge_by_arrival() {
+    let fast = produce(vec![(1, 10), (2, 10), (3, 10)]);
+    let slow = produce(vec![(100, 100)]);
+
+    let vals: Vec<uint> = slow.merge(fast).iter().collect();
+
+    // The slow stream is listed first, but its value arrives last
+    assert_eq!([1, 2, 3, 100].as_slice(), vals.as_slice());
+}
+
+#[test]
+pub fn test_stream_merge_queues() {
+    let first = LinkedQueue::new();
+    let second = LinkedQueue::new();
+
+    let stream = Stream::<uint, ()>::from_queue(first.clone())
+        .merge(Stream::from_queue(second.clone()));
+
+    // Interest is registered in both queues while they are still empty
+    let (a, b) = (first.clone(), second.clone());
+
+    spawn(move || {
+        sleep(10);
+        b.put(10);
+        a.put(1);
+        b.put(11);
+        b.close();
+        sleep(10);
+        a.put(2);
+        a.close();
+    });
+
+    let mut vals: Vec<uint> = stream.iter().collect();
+
+    vals.sort();
+    assert_eq!([1, 2, 10, 11].as_slice(), vals.as_slice());
+    assert!(first.is_empty() && second.is_empty());
+}
+
+#[test]
+pub fn test_stream_merge_error() {
+    let (stream, generate) = Stream::<uint, &'static str>::pair();
+
+    generate.receive(move |res| {
+        res.unwrap().fail("nope");
+    });
+
+    let (other, _g) = Stream::<uint, &'static str>::pair();
+
+    let res = other.merge(stream).await();
+    assert_eq!("nope", res.unwrap_err().unwrap());
+}
@@ -0,0 +1,28 @@
+use syncbox::util::async::*;
+use super::nums;
+
+#[test]
+pub fn test_stream_peek() {
+    let (head, stream) = nums(0, 3).peek().await().unwrap();
+    assert_eq!(Some(0), head);
+
+    let vals: Vec<uint> = stream.iter().collect();
+    assert_eq!([0, 1, 2].as_slice(), vals.as_slice());
+}
+
+#[test]
+pub fn test_stream_peek_done() {
+    let (head, stream) = nums(0, 0).peek().await().unwrap();
+    assert_eq!(None, head);
+
+    assert!(stream.await().unwrap().is_none());
+}
+
+#[test]
+pub fn test_stream_peek_error() {
+    let (stream, generate) = Stream::<uint, &'static str>::pair();
+
+    generate.receive(move |g| g.unwrap().fail("nope"));
+
+    assert_eq!("nope", stream.peek().await().unwrap_err().unwrap());
+}
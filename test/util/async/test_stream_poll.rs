@@ -0,0 +1,86 @@
+use syncbox::util::async::*;
+use super::spawn;
+use std::sync::mpsc::channel;
+
+#[test]
+pub fn test_poll_before_send() {
+    let (s, p) = Stream::<uint, ()>::pair();
+
+    let s = s.poll().unwrap_err();
+    assert!(!s.is_ready());
+
+    p.send(1);
+
+    match s.poll().unwrap() {
+        Ok(Some((v, _))) => assert_eq!(1, v),
+        _ => panic!("unexpected value"),
+    }
+}
+
+#[test]
+pub fn test_poll_after_done() {
+    let (s, p) = Stream::<uint, ()>::pair();
+
+    p.done();
+
+    // Nothing is sent until the consumer requests the value
+    let s = s.poll().unwrap_err();
+
+    match s.await() {
+        Ok(None) => {}
+        _ => panic!("expected the stream to be done"),
+    }
+}
+
+#[test]
+pub fn test_generate_await_consumer_interest() {
+    let (s, p) = Stream::<uint, ()>::pair();
+    let (tx, rx) = channel();
+
+    spawn(move || {
+        match s.await() {
+            Ok(Some((v, _))) => tx.send(v).unwrap(),
+            _ => panic!("unexpected value"),
+        }
+    });
+
+    // Blocks until the consumer is waiting on a value
+    let p = p.await().unwrap();
+    assert!(p.is_ready());
+
+    p.send(1);
+    assert_eq!(1, rx.recv().unwrap());
+}
+
+#[test]
+pub fn test_generate_await_consumer_canceled() {
+    let (s, p) = Stream::<uint, ()>::pair();
+
+    spawn(move || drop(s));
+
+    assert!(p.await().is_err());
+}
+
+#[test]
+pub fn test_try_send_before_previous_consumed() {
+    let (s, p) = Stream::<uint, ()>::pair();
+
+    assert!(p.try_send(1).is_ok());
+
+    // The first value has not been consumed, the second one is handed back
+    assert_eq!(Err(2), p.try_send(2));
+
+    match s.poll().unwrap() {
+        Ok(Some((v, _))) => assert_eq!(1, v),
+        _ => panic!("unexpected value"),
+    }
+}
+
+#[test]
+#[should_fail(expected = "the previous value has not been consumed yet")]
+pub fn test_send_before_previous_consumed() {
+    let (_s, p) = Stream::<uint, ()>::pair();
+
+    p.send(1);
+    p.send(2);
+}
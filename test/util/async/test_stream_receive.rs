@@ -1,9 +1,87 @@
 use syncbox::util::async::*;
 use std::sync::mpsc::{channel, Sender};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use super::{spawn, sleep};
 
 #[test]
 pub fn test_one_shot_stream_async() {
+    let (stream, producer) = Stream::<&'static str, ()>::pair();
+    let (tx, rx) = channel();
+
+    stream.receive(move |res| {
+        match res {
+            Ok(Some((h, _))) => tx.send(h).unwrap(),
+            _ => panic!("nope"),
+        }
+    });
+
+    assert!(rx.try_recv().is_err());
+
+    producer.send("hello");
+    assert_eq!("hello", rx.recv().unwrap());
+}
+
+#[test]
+pub fn test_producer_receive_when_consumer_cb_set() {
+    let (stream, producer) = Stream::<&'static str, ()>::pair();
+    let (tx, rx) = channel();
+    let (txp, rxp) = channel();
+
+    producer.receive(move |p| {
+        txp.send(p.is_ok()).unwrap();
+        p.unwrap().send("hello");
+    });
+
+    // The consumer has not registered interest yet
+    assert!(rxp.try_recv().is_err());
+
+    stream.receive(move |res| {
+        match res {
+            Ok(Some((h, _))) => tx.send(h).unwrap(),
+            _ => panic!("nope"),
+        }
+    });
+
+    // Registering the consumer callback notifies the producer
+    assert!(rxp.try_recv().unwrap());
+    assert_eq!("hello", rx.recv().unwrap());
+}
+
+#[test]
+pub fn test_producer_receive_after_consumer_cb_set() {
+    let (stream, producer) = Stream::<&'static str, ()>::pair();
+    let (tx, rx) = channel();
+    let (txp, rxp) = channel();
+
+    stream.receive(move |res| {
+        match res {
+            Ok(Some((h, _))) => tx.send(h).unwrap(),
+            _ => panic!("nope"),
+        }
+    });
+
+    assert!(producer.is_ready());
+
+    // The consumer is already waiting, the callback is invoked right away
+    producer.receive(move |p| {
+        txp.send(p.is_ok()).unwrap();
+        p.unwrap().send("hello");
+    });
+
+    assert!(rxp.try_recv().unwrap());
+    assert_eq!("hello", rx.recv().unwrap());
+}
+
+#[test]
+pub fn test_producer_receive_when_consumer_canceled() {
+    let (stream, producer) = Stream::<&'static str, ()>::pair();
+    let (txp, rxp) = channel();
+
+    producer.receive(move |p| txp.send(p.is_ok()).unwrap());
+
+    drop(stream);
+    assert!(!rxp.recv().unwrap());
 }
 
 #[test]
@@ -167,6 +245,61 @@ pub fn test_stream_produce_interest_before_receive_async() {
 
 #[test]
 pub fn test_stream_produce_before_receive_async() {
+    let (stream, producer) = Stream::<&'static str, ()>::pair();
+    let (tx, rx) = channel();
+
+    producer.send("hello");
+
+    stream.receive(move |res| {
+        match res {
+            Ok(Some((h, _))) => tx.send(h).unwrap(),
+            _ => panic!("nope"),
+        }
+    });
+
+    // The value is already present, the callback is invoked right away
+    assert_eq!("hello", rx.try_recv().unwrap());
+}
+
+#[test]
+pub fn test_producer_awaits_demand_before_each_send() {
+    let (mut stream, generate) = Stream::<uint, ()>::pair();
+    let sent = Arc::new(AtomicUsize::new(0));
+
+    {
+        let sent = sent.clone();
+
+        spawn(move || {
+            let mut generate = generate;
+
+            for i in range(0, 5u) {
+                // Wait until the consumer requests the next value
+                generate = generate.await().unwrap();
+
+                sent.fetch_add(1, Ordering::SeqCst);
+                generate.send(i);
+            }
+
+            generate.await().unwrap().done();
+        });
+    }
+
+    let mut received = 0;
+
+    while let Ok(Some((v, rest))) = stream.await() {
+        assert_eq!(received, v);
+        received += 1;
+
+        // Give the producer a chance to run ahead
+        sleep(10);
+
+        // The producer never sends more than what was requested
+        assert_eq!(received, sent.load(Ordering::SeqCst));
+
+        stream = rest;
+    }
+
+    assert_eq!(5, received);
 }
 
 #[test]
@@ -1,3 +1,4 @@
+use syncbox::util::async::*;
 use super::nums;
 
 #[test]
@@ -5,3 +6,32 @@ pub fn test_stream_reduce_async() {
     let s = nums(0, 5).reduce(10, move |sum, v| sum + v);
     assert_eq!(20, s.await().unwrap());
 }
+
+#[test]
+pub fn test_stream_reduce_sum() {
+    let s = nums(0, 5).reduce(0, move |sum, v| sum + v);
+    assert_eq!(10, s.await().unwrap());
+}
+
+#[test]
+pub fn test_stream_reduce_empty() {
+    let s = nums(0, 0).reduce(7, move |sum, v| sum + v);
+    assert_eq!(7, s.await().unwrap());
+}
+
+#[test]
+pub fn test_stream_reduce_error() {
+    let (stream, generate) = Stream::<uint, &'static str>::pair();
+
+    generate.receive(move |res| {
+        let generate = res.unwrap();
+        generate.send(1);
+
+        generate.receive(move |res| {
+            res.unwrap().fail("nope");
+        });
+    });
+
+    let res = stream.reduce(0, move |sum, v| sum + v).await();
+    assert_eq!("nope", res.unwrap_err().unwrap());
+}
@@ -0,0 +1,40 @@
+use syncbox::util::async::*;
+use super::nums;
+
+#[test]
+pub fn test_stream_scan_running_sum() {
+    let stream = nums(0, 4).scan(0, move |sum, v| {
+        *sum += v;
+        Some(*sum)
+    });
+
+    let vals: Vec<uint> = stream.iter().collect();
+    assert_eq!([0, 1, 3, 6].as_slice(), vals.as_slice());
+}
+
+#[test]
+pub fn test_stream_scan_ends_early() {
+    // Yields values until one is not greater than the previous one
+    let stream = Stream::<uint, ()>::from_iter(vec![1, 3, 5, 4, 7].into_iter())
+        .scan(0, move |prev, v| {
+            if v > *prev {
+                *prev = v;
+                Some(v)
+            } else {
+                None
+            }
+        });
+
+    let vals: Vec<uint> = stream.iter().collect();
+    assert_eq!([1, 3, 5].as_slice(), vals.as_slice());
+}
+
+#[test]
+pub fn test_stream_scan_error() {
+    let (stream, generate) = Stream::<uint, &'static str>::pair();
+
+    generate.receive(move |g| g.unwrap().fail("nope"));
+
+    let res = stream.scan(0u, move |_, v| Some(v)).await();
+    assert_eq!("nope", res.unwrap_err().unwrap());
+}
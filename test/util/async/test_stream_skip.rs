@@ -0,0 +1,17 @@
+use super::nums;
+
+#[test]
+pub fn test_stream_skip() {
+    let stream = nums(0, 5).skip(2);
+    let vals: Vec<uint> = stream.iter().collect();
+
+    assert_eq!([2, 3, 4].as_slice(), vals.as_slice());
+}
+
+#[test]
+pub fn test_stream_skip_past_end() {
+    let stream = nums(0, 3).skip(10);
+    let vals: Vec<uint> = stream.iter().collect();
+
+    assert!(vals.is_empty());
+}
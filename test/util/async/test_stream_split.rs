@@ -0,0 +1,52 @@
+use syncbox::util::async::*;
+use std::sync::mpsc::channel;
+use super::{nums, spawn};
+
+#[test]
+pub fn test_stream_split() {
+    let (s1, s2) = nums(0, 3).split();
+    let (tx, rx) = channel();
+
+    spawn(move || {
+        let vals: Vec<uint> = s2.iter().collect();
+        tx.send(vals).unwrap();
+    });
+
+    let vals: Vec<uint> = s1.iter().collect();
+
+    assert_eq!([0, 1, 2].as_slice(), vals.as_slice());
+    assert_eq!([0, 1, 2].as_slice(), rx.recv().unwrap().as_slice());
+}
+
+#[test]
+pub fn test_stream_split_waits_for_both_consumers() {
+    let (s1, s2) = nums(0, 3).split();
+    let (tx, rx) = channel();
+
+    s1.receive(move |res| {
+        tx.send(res.unwrap().unwrap().0).unwrap();
+    });
+
+    // The second consumer has not requested a value yet
+    assert!(rx.try_recv().is_err());
+
+    s2.receive(move |_| {});
+    assert_eq!(0, rx.recv().unwrap());
+}
+
+#[test]
+pub fn test_stream_split_error() {
+    let (stream, generate) = Stream::<uint, &'static str>::pair();
+    let (s1, s2) = stream.split();
+
+    generate.receive(move |g| g.unwrap().fail("nope"));
+
+    let (tx, rx) = channel();
+
+    spawn(move || {
+        tx.send(s2.await().unwrap_err().unwrap()).unwrap();
+    });
+
+    assert_eq!("nope", s1.await().unwrap_err().unwrap());
+    assert_eq!("nope", rx.recv().unwrap());
+}
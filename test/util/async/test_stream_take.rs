@@ -1,4 +1,5 @@
 use syncbox::util::async::Future;
+use std::sync::mpsc::channel;
 use super::nums;
 
 #[test]
@@ -29,3 +30,38 @@ pub fn test_stream_take_until() {
     c.complete("done");
     assert!(iter.next().is_none());
 }
+
+/*
+ *
+ * ===== Stream::take_while(...) =====
+ *
+ */
+
+#[test]
+pub fn test_stream_take_while() {
+    let stream = nums(0, 10).take_while(|&i| i < 4);
+    let vals: Vec<usize> = stream.iter().collect();
+
+    assert_eq!([0, 1, 2, 3].as_slice(), vals.as_slice());
+}
+
+#[test]
+pub fn test_stream_take_while_stops_driving_upstream() {
+    let (tx, rx) = channel();
+
+    let stream = nums(0, 10)
+        .map(move |i| { tx.send(i).unwrap(); i })
+        .take_while(|&i| i < 2);
+
+    let vals: Vec<usize> = stream.iter().collect();
+    assert_eq!([0, 1].as_slice(), vals.as_slice());
+
+    // The first non matching value is the last one pulled from upstream
+    let mut pulled = vec![];
+
+    while let Ok(i) = rx.try_recv() {
+        pulled.push(i);
+    }
+
+    assert_eq!([0, 1, 2].as_slice(), pulled.as_slice());
+}
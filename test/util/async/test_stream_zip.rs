@@ -0,0 +1,32 @@
+use syncbox::util::async::*;
+use super::nums;
+
+#[test]
+pub fn test_stream_zip() {
+    let stream = nums(0, 3).zip(nums(10, 13));
+    let vals: Vec<(uint, uint)> = stream.iter().collect();
+
+    assert_eq!([(0, 10), (1, 11), (2, 12)].as_slice(), vals.as_slice());
+}
+
+#[test]
+pub fn test_stream_zip_uneven() {
+    let stream = nums(0, 2).zip(nums(10, 20));
+    let vals: Vec<(uint, uint)> = stream.iter().collect();
+
+    assert_eq!([(0, 10), (1, 11)].as_slice(), vals.as_slice());
+}
+
+#[test]
+pub fn test_stream_zip_error() {
+    let (stream, generate) = Stream::<uint, &'static str>::pair();
+
+    generate.receive(move |res| {
+        res.unwrap().fail("nope");
+    });
+
+    let other: Stream<uint, &'static str> = Future::of(None).as_stream();
+
+    let res = stream.zip(other).await();
+    assert_eq!("nope", res.unwrap_err().unwrap());
+}